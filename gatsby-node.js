@@ -2,6 +2,7 @@ exports.createPages = ({ actions, graphql }) => {
     const { createPage } = actions
   
     const blogPostTemplate = require.resolve(`./src/templates/staticTemplate.js`)
+    const paperTemplate = require.resolve(`./src/templates/paperTemplate.js`)
   
     return graphql(`
       {
@@ -23,7 +24,7 @@ exports.createPages = ({ actions, graphql }) => {
         return Promise.reject(result.errors)
       }
   
-      return result.data.allMarkdownRemark.edges.forEach(({ node }) => {
+      result.data.allMarkdownRemark.edges.forEach(({ node }) => {
         createPage({
           path: node.frontmatter.slug,         
           component: blogPostTemplate,
@@ -34,6 +35,15 @@ exports.createPages = ({ actions, graphql }) => {
           },
         })
       })
+
+      // a page per paper, which the sitemap lists
+      require(`./papers.json`).forEach(paper => {
+        createPage({
+          path: `/papers/${paper.id}/`,
+          component: paperTemplate,
+          context: { paper },
+        })
+      })
     })
   }
   
\ No newline at end of file
@@ -5,8 +5,10 @@ const yaml = require("js-yaml");
 const fs = require("fs");
 
 const papers = fs.readdirSync(paper_dir);
-const paper_objs = papers.map((paper) =>
-  yaml.load(fs.readFileSync(paper_dir + "/" + paper, { encoding: "utf-8" }))
-);
+// the id of a paper is the name of its file, which names its page
+const paper_objs = papers.map((paper) => ({
+  id: paper.replace(/\.yml$/, ""),
+  ...yaml.load(fs.readFileSync(paper_dir + "/" + paper, { encoding: "utf-8" })),
+}));
 
 fs.writeFileSync(outputJSON, JSON.stringify(paper_objs, null, null));
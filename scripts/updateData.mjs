@@ -1,152 +1,1779 @@
-import axios from "axios";
+import assert from "assert";
+import { execFileSync } from "child_process";
 import fs from "fs";
 import yaml from "js-yaml";
-import { XMLParser } from "fast-xml-parser";
-import fastls from "fast-levenshtein";
+import path from "path";
+import {
+  allSources,
+  bibtexSource,
+  ask,
+  canonicalizeVenue,
+  checkBibtex,
+  checkLink,
+  cleanBibtex,
+  closePrompt,
+  config,
+  configure,
+  dedupePublications,
+  enabledSources,
+  fetchPendingBibtex,
+  fieldValues,
+  formatPaper,
+  hasPendingBibtex,
+  get,
+  hasCompleteMetadata,
+  isPreprint,
+  lastNameKey,
+  log,
+  mapConcurrent,
+  missingBibtex,
+  missingCredentials,
+  paperBibtex,
+  paperHash,
+  paperSlugs,
+  parseArxivFeed,
+  parseBibtex,
+  rankMatches,
+  relevance,
+  resetField,
+  resettableFields,
+  searchArxiv,
+  setStatus,
+  similarityScore,
+  sortPublications,
+  suggestLabels,
+  unifiedDiff,
+  unknownFields,
+  unknownLabels,
+  updatePaper,
+  UpdaterError,
+  validatePaper,
+  verifyPaper,
+  withoutStatus,
+} from "./updater.mjs";
 
-const paper_dir = "papers";
-const papers = fs.readdirSync(paper_dir);
+const args = process.argv.slice(2);
 
-async function updateFromArxiv(paper) {
-  let info = await axios.get(
-    "http://export.arxiv.org/api/query?search_query=" +
-      paper.title.replace("-", " ").split(" ").join("+"),
-    { timeout: 30000 }
+// Returns the value following the given option, e.g. `--cache-ttl 3`.
+function option(name, fallback) {
+  const i = args.indexOf(name);
+  return i === -1 || i + 1 === args.length ? fallback : args[i + 1];
+}
+
+// Returns the number following the given option; exits with an error if it
+// is not a number or not valid (by default, negative).
+function numberOption(name, fallback, valid = (value) => value >= 0) {
+  const value = Number(option(name, fallback));
+  if (isNaN(value) || !valid(value)) {
+    log.error("Invalid value of " + name + ": " + option(name, fallback));
+    process.exit(1);
+  }
+  return value;
+}
+
+const paper_dir = option("--papers-dir", "papers");
+// sorted, since the order of the directory entries depends on the file
+// system, and runs should process the papers in the same order everywhere
+const papers = fs
+  .readdirSync(paper_dir)
+  .filter((file) => file.endsWith(".yml"))
+  .sort();
+// Generated files (reports, index, feed, cache, backups and run state) are
+// written to this directory.
+const outputDir = option("--output-dir", ".");
+fs.mkdirSync(outputDir, { recursive: true });
+
+// Print the changes instead of writing them to the paper files.
+const dryRun = args.includes("--dry-run");
+// Run the updates without writing anything and print how well the papers
+// match instead.
+const statsOnly = args.includes("--stats-only");
+// Run the updates without writing anything and print how the fields of the
+// papers differ from their version at this git ref instead.
+const diffAgainst = option("--diff-against", undefined);
+// Only check the paper files, exit with a non-zero code if any is invalid.
+const validateOnly = args.includes("--validate-only");
+// Rewrite all paper files in the canonical format instead of updating.
+const formatMode = args.includes("--format");
+// Only check that all paper files are in the canonical format.
+const checkFormat = args.includes("--check-format");
+// Remove the volatile fields from the stored bibtex of all papers and lay it
+// out like DBLP does instead of updating.
+const normalizeBibtex = args.includes("--normalize-bibtex");
+// Check that the title pairs in similarity_cases.yml are still classified as
+// expected instead of updating.
+const checkSimilarity = args.includes("--check-similarity");
+// Print how many of the title pairs in similarity_cases.yml would match at a
+// range of thresholds, with precision and recall, instead of updating.
+const thresholdSweep = args.includes("--threshold-sweep");
+// Time the title similarity of all pairs of paper titles and of the pairs in
+// similarity_cases.yml with and without the min_shared_words filter, and
+// print the scores it changes, instead of updating.
+const benchmarkSimilarity = args.includes("--benchmark-similarity");
+// Write all papers to a single JSON index instead of updating.
+const buildIndex = args.includes("--build-index");
+// After updating, also write the JSON index as --build-index does.
+const buildAfterUpdate = args.includes("--build-after-update");
+// Write the ids of the papers per author and per venue to authors.json and
+// venues.json instead of updating.
+const buildAggregates = args.includes("--build-aggregates");
+// Write the bibtex of all papers to a single file instead of updating.
+const buildBibtex = args.includes("--build-bibtex");
+// Replace labels that are not in labels.yml by the closest label of the
+// vocabulary, where it differs by one character, after confirmation.
+const fixLabels = args.includes("--fix-labels");
+// Merge the second of the two paper files given after --merge into the
+// first, after confirmation, and remove it.
+const mergeFiles = (() => {
+  const i = args.indexOf("--merge");
+  return i === -1 ? undefined : args.slice(i + 1, i + 3);
+})();
+// Print labels that might apply to the papers instead of updating.
+const suggestLabelsMode = args.includes("--suggest-labels");
+// Write an Atom feed of the most recently published papers instead of
+// updating.
+const buildFeed = args.includes("--build-feed");
+// Write a sitemap with the website and the page of each paper, dated by their
+// latest publication, instead of updating.
+const buildSitemap = args.includes("--build-sitemap");
+// Print how many papers lack which metadata and write the gaps per paper to
+// coverage.csv instead of updating.
+const coverage = args.includes("--coverage");
+// Write the title, authors, labels, venue, year and arXiv url of all papers
+// to papers.csv instead of updating.
+const exportCsv = args.includes("--export-csv");
+// Print recent arXiv preprints on the discovery terms that are not in the
+// collection instead of updating.
+const discover = args.includes("--discover");
+// Compare the stored data with the arXiv entries and DBLP records the papers
+// are pinned to and print the differences instead of updating.
+const verify = args.includes("--verify");
+// Check that the urls of all publications are reachable instead of updating.
+const checkLinks = args.includes("--check-links");
+// Delete the *.yml.bak files that older versions left in the paper directory.
+const cleanBackups = args.includes("--clean-backups");
+// Number of backups that are kept per paper file.
+const keepBackups = numberOption("--keep-backups", 5);
+// Skip the quadratic search for duplicate papers.
+const noDupCheck = args.includes("--no-dup-check");
+// Title similarity above which two papers are reported as duplicates.
+const dupThreshold = numberOption(
+  "--dup-threshold",
+  0.9,
+  (threshold) => threshold >= 0 && threshold <= 1
+);
+// Only print the final summary (and the output of the reporting modes).
+const quiet = args.includes("--quiet");
+// Only update the paper file with this name, or the files matching this glob
+// (like "Lin*.yml"); the other modes and the duplicate check still see all.
+const paperPattern = option("--paper", undefined);
+// Remove this field from the papers (those --paper selects, or all), or from
+// their publications, so that the next update fills it in anew; only with
+// --confirm, otherwise print the changes.
+const resetFieldName = option("--reset-field", undefined);
+const confirm = args.includes("--confirm");
+// Only fetch the missing bibtex of peer-reviewed publications from DBLP,
+// directly by their DBLP key where they have one.
+const onlyMissingBibtex = args.includes("--only-missing-bibtex");
+// Skip papers with complete metadata that did not change since the last run.
+const onlyNew = args.includes("--only-new");
+// Bypass the response cache, but still refresh it with the fetched responses.
+const noCache = args.includes("--no-cache");
+// Maximal age of a cached response in days.
+const cacheTtl = numberOption("--cache-ttl", 7);
+// Number of retries of requests that failed with a transient error.
+const maxRetries = numberOption("--retries", 3);
+// Ask before applying matches whose similarity is in the uncertain band.
+const interactive = args.includes("--interactive");
+// Answer all requests from the responses in this directory instead of the
+// network, for deterministic runs.
+const fixturesDir = option("--fixtures", undefined);
+// Minutes after which no further papers are started; the papers in progress
+// are finished and saved, the others are left for the next run.
+const deadlineMinutes = numberOption(
+  "--deadline",
+  Infinity,
+  (minutes) => minutes > 0
+);
+const deadline = Date.now() + deadlineMinutes * 60 * 1000;
+// Number of errors (like failed requests to a source) above which the run
+// exits with an error code; it is completed and reported either way.
+// `--fail-on-error` is `--max-errors 0`, the default.
+const maxErrors = args.includes("--fail-on-error")
+  ? 0
+  : numberOption("--max-errors", 0);
+
+configure({
+  cacheDir: path.join(outputDir, ".updater-cache"),
+  noCache,
+  cacheTtl,
+  maxRetries,
+  fixturesDir,
+  interactive,
+  // runs that write the papers save them again once the bibtex is there,
+  // the others fetch it right away
+  deferBibtex: !dryRun && !statsOnly && diffAgainst === undefined,
+  ...(quiet ? { logLevel: "none" } : {}),
+});
+
+const similarityCasesFile = new URL("similarity_cases.yml", import.meta.url);
+const indexFile = path.join(outputDir, "index.json");
+// Incremented on incompatible changes of the index format.
+const indexVersion = 1;
+const authorsFile = path.join(outputDir, "authors.json");
+const venuesFile = path.join(outputDir, "venues.json");
+const bibtexFile = path.join(outputDir, "all.bib");
+const feedFile = path.join(outputDir, "feed.xml");
+const sitemapFile = path.join(outputDir, "sitemap.xml");
+const coverageFile = path.join(outputDir, "coverage.csv");
+const csvFile = path.join(outputDir, "papers.csv");
+// Number of papers in the feed.
+const feedSize = numberOption("--feed-size", 50);
+const siteUrl = config.site_url;
+const backupDir = path.join(outputDir, ".backups");
+
+const concurrency = config.concurrency;
+
+const stats = {
+  papers: 0,
+  errors: 0,
+  mergedPublications: 0,
+  skipped: 0,
+  // papers not processed because the deadline passed
+  remaining: 0,
+  // matched, unmatched and failed papers per source
+  sources: {},
+  // papers that could not be validated or updated, with the reason and the
+  // kind of the error: "invalid" or that of the UpdaterError
+  failed: [],
+  // number of errors by kind
+  errorKinds: {},
+  // stored bibtex that does not fit the paper (see checkBibtex) and arXiv
+  // and DBLP matches that do not fit each other (see crossCheck)
+  mismatches: [],
+  // papers a maintainer should look at, see needsAttention
+  attention: [],
+  // papers sharing a DOI or DBLP record, with the shared identifier
+  duplicates: [],
+  // papers no source matched, with the best title similarity per source
+  noMatch: [],
+};
+const countError = (kind) => {
+  stats.errors++;
+  stats.errorKinds[kind] = (stats.errorKinds[kind] || 0) + 1;
+};
+// like "3 errors (2 fetch, 1 parse)"
+const errorSummary = () =>
+  stats.errors +
+  " errors" +
+  (stats.errors > 0
+    ? " (" +
+      Object.entries(stats.errorKinds)
+        .map(([kind, count]) => count + " " + kind)
+        .join(", ") +
+      ")"
+    : "");
+const reportFile = path.join(outputDir, "report.json");
+// Records when each paper was last processed and its hash (see paperHash).
+const stateFile = path.join(outputDir, ".updater-state.json");
+// Lists the paper files a run has saved, so that an interrupted run can be
+// resumed; removed when a run over all papers completes.
+const journalFile = path.join(outputDir, ".updater-journal");
+
+// Records which source set which field of the papers, see updatePaper, as
+// { path: { field: { source, date } } }. Fields without a record were
+// entered by hand or before the record was kept. Runs over other paper
+// directories share the file, hence the records are kept by path.
+const provenanceFile = path.join(outputDir, "provenance.json");
+let provenance = fs.existsSync(provenanceFile)
+  ? JSON.parse(fs.readFileSync(provenanceFile, { encoding: "utf-8" }))
+  : {};
+
+// The fields of the paper file that sources set, see provenance.
+function provenanceOf(file) {
+  const key = path.join(paper_dir, file);
+  provenance[key] = provenance[key] || {};
+  return provenance[key];
+}
+
+function loadState() {
+  if (!fs.existsSync(stateFile)) {
+    return { papers: {} };
+  }
+  return JSON.parse(fs.readFileSync(stateFile, { encoding: "utf-8" }));
+}
+
+// The statistics of the run in a machine-readable form.
+function statsReport() {
+  return { timestamp: new Date().toISOString(), ...stats };
+}
+
+// Copies the paper file to the backup directory before it gets overwritten,
+// removing all but the most recent backups of that file.
+function backupFile(file) {
+  fs.mkdirSync(backupDir, { recursive: true });
+  const timestamp = new Date().toISOString().replace(/[:.]/g, "-");
+  fs.copyFileSync(
+    paper_dir + "/" + file,
+    backupDir + "/" + file + "." + timestamp + ".bak"
   );
-  let data = info.data;
-  let parser = new XMLParser();
-  let dataObj = parser.parse(data);
-  let hits = [dataObj.feed.entry].flat();
 
-  hits.forEach((hit) => {
-    if (hit === undefined) {
-      return;
+  // the timestamps sort chronologically
+  const backups = fs
+    .readdirSync(backupDir)
+    .filter((name) => name.startsWith(file + ".") && name.endsWith(".bak"))
+    .sort();
+  backups
+    .slice(0, Math.max(0, backups.length - keepBackups))
+    .forEach((name) => fs.unlinkSync(backupDir + "/" + name));
+}
+
+// Replaces the paper file by the content, which is written to a temporary
+// file next to it first, so that a crash leaves the old or the new version
+// and never a truncated file.
+function writePaper(file, content) {
+  const target = paper_dir + "/" + file;
+  const temporary = paper_dir + "/." + file + ".tmp";
+  const fd = fs.openSync(temporary, "w");
+  try {
+    fs.writeFileSync(fd, content);
+    fs.fsyncSync(fd);
+  } finally {
+    fs.closeSync(fd);
+  }
+  fs.renameSync(temporary, target);
+}
+
+if (cleanBackups) {
+  const stale = fs
+    .readdirSync(paper_dir)
+    .filter((file) => file.endsWith(".yml.bak"));
+  stale.forEach((file) => fs.unlinkSync(paper_dir + "/" + file));
+  console.log("Removed " + stale.length + " backup files from " + paper_dir);
+  process.exit(0);
+}
+
+if (checkSimilarity) {
+  const cases = yaml.load(
+    fs.readFileSync(similarityCasesFile, { encoding: "utf-8" })
+  );
+  const failed = cases.filter((pair) => {
+    const threshold = pair.threshold || 0.9;
+    const score = similarityScore(pair.title, pair.candidate);
+    if (score >= threshold === pair.match) {
+      return false;
     }
+    console.log(
+      (pair.match ? "Expected a match" : "Expected no match") +
+        " (similarity " +
+        score.toFixed(3) +
+        ", threshold " +
+        threshold +
+        "):\n  " +
+        pair.title +
+        "\n  " +
+        pair.candidate
+    );
+    return true;
+  });
+  console.log(
+    "Checked " + cases.length + " title pairs, " + failed.length + " failed"
+  );
+  process.exit(failed.length > 0 ? 1 : 0);
+}
 
-    let title = hit.title;
-    if (fastls.get(title, paper.title) < 5) {
-      if (!("authors" in paper)) {
-        paper.authors = hit.author
-          .map((a) => a.name.split(" ").at(-1))
-          .join(", ");
-        console.log(
-          "Setting authors of " + paper.title + " to " + paper.authors
-        );
-      }
+if (thresholdSweep) {
+  const cases = yaml.load(
+    fs.readFileSync(similarityCasesFile, { encoding: "utf-8" })
+  );
+  const scored = cases.map((pair) => ({
+    score: similarityScore(pair.title, pair.candidate),
+    match: pair.match,
+  }));
+  const relevant = scored.filter(({ match }) => match).length;
+  const ratio = (count, total) =>
+    total === 0 ? "-" : (count / total).toFixed(3);
+  console.log("threshold  matches  precision  recall");
+  for (let step = 8; step <= 19; step++) {
+    const threshold = step / 20;
+    const matched = scored.filter(({ score }) => score >= threshold);
+    const correct = matched.filter(({ match }) => match).length;
+    console.log(
+      threshold.toFixed(2).padStart(9) +
+        String(matched.length).padStart(9) +
+        ratio(correct, matched.length).padStart(11) +
+        ratio(correct, relevant).padStart(8)
+    );
+  }
+  console.log(
+    "Scored " + cases.length + " title pairs, " + relevant + " of them matches"
+  );
+  process.exit(0);
+}
 
-      let date = new Date(hit.published);
-      let year = date.getFullYear();
-      let month = date.getMonth();
-      let day = date.getDate();
-      let pdfurl =
-        hit.id
-          //.replace("abs", "pdf")
-          .replace(/v\d+/, "")
-          .replace("http", "https");// + ".pdf";
-
-      if (!paper.publications.some((pub) => pub.name === "arXiv")) {
-        console.log("Added arXiv preprint to " + paper.title);
-        paper.publications.push({
-          name: "arXiv",
-          year,
-          month,
-          day,
-          url: pdfurl,
-        });
-      } else {
-        let publ_index = paper.publications.findIndex(
-          (pub) => pub.name === "arXiv"
-        );
-        paper.publications[publ_index] = {
-          ...paper.publications[publ_index],
-          url: pdfurl,
-          year,
-          month,
-          day,
-        };
+let entries = [];
+papers.forEach((file) => {
+  const path = paper_dir + "/" + file;
+  const original = fs.readFileSync(path, { encoding: "utf-8" });
+  let violations;
+  let paper;
+  try {
+    paper = yaml.load(original);
+    violations = validatePaper(paper);
+  } catch (error) {
+    violations = ["invalid YAML: " + error.message];
+  }
+
+  if (violations.length > 0) {
+    countError("invalid");
+    stats.failed.push({
+      file,
+      reason: "invalid: " + violations.join("; "),
+      kind: "invalid",
+    });
+    log.error(
+      "invalid paper " +
+        path +
+        ":" +
+        violations.map((violation) => "\n  " + violation).join("")
+    );
+  } else {
+    entries.push({ file, original, paper });
+  }
+});
+
+// Fields the updater does not know may be typos or leftovers of an older
+// format; they are kept, but a maintainer should look at them.
+entries.forEach(({ file, paper }) => {
+  unknownFields(paper).forEach((field) => {
+    const reason = "unknown field " + field;
+    stats.attention.push({ file, reason });
+    log.warn(paper_dir + "/" + file + ": " + reason);
+  });
+});
+
+// Labels outside the vocabulary fragment the filters of the website.
+let unknownLabelCount = 0;
+entries.forEach(({ file, paper }) => {
+  unknownLabels(paper).forEach(({ label, suggestion }) => {
+    unknownLabelCount++;
+    const reason =
+      "unknown label " + label + " (did you mean " + suggestion + "?)";
+    stats.attention.push({ file, reason });
+    log.warn(paper_dir + "/" + file + ": " + reason);
+  });
+});
+
+if (validateOnly) {
+  console.log(
+    "Validated " +
+      papers.length +
+      " papers, " +
+      stats.errors +
+      " invalid, " +
+      unknownLabelCount +
+      " unknown labels"
+  );
+  process.exit(stats.errors > 0 || unknownLabelCount > 0 ? 1 : 0);
+}
+
+if (fixLabels) {
+  if (!process.stdin.isTTY) {
+    log.error("--fix-labels needs a terminal to ask for confirmations");
+    process.exit(1);
+  }
+  let fixed = 0;
+  for (const { file, paper } of entries) {
+    let labels = paper.labels;
+    for (const { label, suggestion, distance } of unknownLabels(paper)) {
+      if (distance > 1) {
+        continue;
+      }
+      const answer = await ask(
+        paper_dir +
+          "/" +
+          file +
+          ": replace label " +
+          label +
+          " by " +
+          suggestion +
+          "? [y/N] "
+      );
+      if (answer.trim().toLowerCase().startsWith("y")) {
+        labels = labels.map((other) => (other === label ? suggestion : other));
+        fixed++;
       }
     }
+    if (labels !== paper.labels) {
+      paper.labels = [...new Set(labels)];
+      backupFile(file);
+      writePaper(file, formatPaper(paper));
+    }
+  }
+  closePrompt();
+  console.log("Fixed " + fixed + " labels");
+  process.exit(0);
+}
+
+if (mergeFiles !== undefined) {
+  const [kept, removed] = mergeFiles.map((name) =>
+    entries.find(({ file }) => file === path.basename(name))
+  );
+  if (mergeFiles.length < 2 || kept === undefined || removed === undefined) {
+    log.error("--merge needs two valid paper files of " + paper_dir);
+    process.exit(1);
+  }
+  if (kept === removed) {
+    log.error("--merge needs two different paper files");
+    process.exit(1);
+  }
+  if (!dryRun && !process.stdin.isTTY) {
+    log.error("--merge needs a terminal to ask for confirmation");
+    process.exit(1);
+  }
+  const [a, b] = [kept.paper, removed.paper];
+  const authorCount = (paper) =>
+    typeof paper.authors === "string" ? paper.authors.split(",").length : 0;
+  // the fields of the first file win, unless the second one knows more
+  const merged = { ...b, ...a };
+  merged.title = b.title.length > a.title.length ? b.title : a.title;
+  if (authorCount(b) > authorCount(a)) {
+    merged.authors = b.authors;
+  }
+  merged.labels = [...new Set([...a.labels, ...b.labels])];
+  merged.publications = [...a.publications, ...b.publications];
+  if (a.match_hint !== undefined && b.match_hint !== undefined) {
+    merged.match_hint = { ...b.match_hint, ...a.match_hint };
+  }
+  const count = dedupePublications(merged);
+  sortPublications(merged);
+  const content = formatPaper(merged);
+  console.log(unifiedDiff(paper_dir + "/" + kept.file, kept.original, content));
+  console.log(
+    "Merged " +
+      count +
+      " duplicate publications; " +
+      paper_dir +
+      "/" +
+      removed.file +
+      " is removed"
+  );
+  if (dryRun) {
+    process.exit(0);
+  }
+  const answer = await ask("Write the merged paper? [y/N] ");
+  closePrompt();
+  if (!answer.trim().toLowerCase().startsWith("y")) {
+    process.exit(0);
+  }
+  backupFile(kept.file);
+  backupFile(removed.file);
+  writePaper(kept.file, content);
+  fs.unlinkSync(paper_dir + "/" + removed.file);
+  console.log("Merged " + removed.file + " into " + kept.file);
+  process.exit(0);
+}
+
+if (formatMode || checkFormat) {
+  const unformatted = entries.filter(
+    ({ original, paper }) => original !== formatPaper(paper)
+  );
+  unformatted.forEach(({ file, original, paper }) => {
+    const content = formatPaper(paper);
+    if (checkFormat) {
+      console.log(paper_dir + "/" + file + " is not formatted");
+    } else if (dryRun) {
+      console.log(unifiedDiff(paper_dir + "/" + file, original, content));
+    } else {
+      backupFile(file);
+      writePaper(file, content);
+    }
+  });
+  console.log(
+    (checkFormat ? "Found " : "Formatted ") +
+      unformatted.length +
+      " of " +
+      entries.length +
+      " papers" +
+      (checkFormat ? " that are not formatted" : "")
+  );
+  process.exit(stats.errors > 0 || (checkFormat && unformatted.length) ? 1 : 0);
+}
+
+// Whether --paper selects the paper file, by its name or a glob.
+function selected(file) {
+  if (paperPattern === undefined) {
+    return true;
+  }
+  // the pattern may name the file with the papers directory
+  const prefix = paper_dir + "/";
+  const name = paperPattern.startsWith(prefix)
+    ? paperPattern.slice(prefix.length)
+    : paperPattern;
+  const glob = new RegExp(
+    "^" +
+      name
+        .replace(/[.+^${}()|[\]\\]/g, "\\$&")
+        .replace(/\*/g, "[^/]*")
+        .replace(/\?/g, "[^/]") +
+      "$"
+  );
+  return glob.test(file);
+}
+
+if (resetFieldName !== undefined) {
+  if (!resettableFields.includes(resetFieldName)) {
+    log.error(
+      "--reset-field takes one of " +
+        resettableFields.join(", ") +
+        ", not " +
+        resetFieldName
+    );
+    process.exit(1);
+  }
+  let cleared = 0;
+  let changedPapers = 0;
+  const chosen = entries.filter(({ file }) => selected(file));
+  chosen.forEach(({ file, original, paper }) => {
+    const removed = resetField(paper, resetFieldName);
+    if (removed === 0) {
+      return;
+    }
+    // like approximate_date without a month
+    const violations = validatePaper(paper);
+    if (violations.length > 0) {
+      countError("invalid");
+      log.error(
+        "Not resetting " +
+          resetFieldName +
+          " of " +
+          paper_dir +
+          "/" +
+          file +
+          ": " +
+          violations.join("; ")
+      );
+      return;
+    }
+    cleared += removed;
+    changedPapers++;
+    const content = formatPaper(paper);
+    if (!confirm) {
+      console.log(unifiedDiff(paper_dir + "/" + file, original, content));
+      return;
+    }
+    backupFile(file);
+    writePaper(file, content);
+    // the values no longer come from a source
+    const fields = provenanceOf(file);
+    Object.keys(fields)
+      .filter(
+        (key) => key === resetFieldName || key.endsWith(" " + resetFieldName)
+      )
+      .forEach((key) => delete fields[key]);
   });
+  if (confirm) {
+    fs.writeFileSync(
+      provenanceFile,
+      JSON.stringify(provenance, null, 2) + "\n"
+    );
+  }
+  console.log(
+    (confirm ? "Cleared " : "Would clear ") +
+      cleared +
+      " values of " +
+      resetFieldName +
+      " in " +
+      changedPapers +
+      " of " +
+      chosen.length +
+      " papers" +
+      (confirm ? "" : ", run with --confirm to write the papers")
+  );
+  process.exit(stats.errors > 0 ? 1 : 0);
 }
 
-async function updateFromDBLP(paper) {
-  let info = await axios.get(
-    "https://dblp.org/search/publ/api?q=" +
-      paper.title.replace("-", " ").split(" ").join("+"),
-    { timeout: 30000 }
+if (normalizeBibtex) {
+  let normalized = 0;
+  let changedPapers = 0;
+  entries.forEach(({ file, original, paper }) => {
+    const before = normalized;
+    paper.publications
+      .filter((pub) => typeof pub.bibtex === "string")
+      .forEach((pub) => {
+        const cleaned = cleanBibtex(pub.bibtex);
+        if (cleaned !== pub.bibtex) {
+          pub.bibtex = cleaned;
+          normalized++;
+        }
+      });
+    if (normalized === before) {
+      return;
+    }
+    changedPapers++;
+    const content = formatPaper(paper);
+    if (dryRun) {
+      console.log(unifiedDiff(paper_dir + "/" + file, original, content));
+    } else {
+      backupFile(file);
+      writePaper(file, content);
+    }
+  });
+  console.log(
+    "Normalized " +
+      normalized +
+      " bibtex entries in " +
+      changedPapers +
+      " of " +
+      entries.length +
+      " papers"
   );
+  process.exit(stats.errors > 0 ? 1 : 0);
+}
 
-  let data = info.data;
-  let parser = new XMLParser();
-  let dataObj = parser.parse(data);
-  let hits = [dataObj.result.hits.hit].flat();
+// Writes the papers of the entries to the JSON index, most recent first.
+function writeIndex(entries) {
+  const latestYear = (paper) =>
+    Math.max(...paper.publications.map((pub) => pub.year));
+  const identified = entries.map(({ file, paper }) => ({
+    id: file.replace(/\.yml$/, ""),
+    paper,
+  }));
+  const slugs = paperSlugs(identified);
+  const indexed = identified
+    .map(({ id, paper }) => ({ id, slug: slugs.get(id), ...paper }))
+    .sort(
+      (a, b) => latestYear(b) - latestYear(a) || a.title.localeCompare(b.title)
+    );
+  fs.writeFileSync(
+    indexFile,
+    JSON.stringify({ version: indexVersion, papers: indexed })
+  );
+  console.log("Wrote " + indexed.length + " papers to " + indexFile);
+}
 
-  hits.forEach((hit) => {
-    if (hit === undefined || hit.info.venue === "CoRR") {
+if (benchmarkSimilarity) {
+  const cases = yaml.load(
+    fs.readFileSync(similarityCasesFile, { encoding: "utf-8" })
+  );
+  const titles = entries.map(({ paper }) => paper.title);
+  const pairs = [
+    ...titles.flatMap((a, i) => titles.slice(i + 1).map((b) => [a, b])),
+    ...cases.map((pair) => [pair.title, pair.candidate]),
+  ];
+  const configured = config.min_shared_words;
+  // The scores of all pairs with the given min_shared_words; prints the time
+  // of the fastest of a few rounds, which leaves out the warm-up.
+  const score = (minSharedWords) => {
+    config.min_shared_words = minSharedWords;
+    let fastest = Infinity;
+    let scores;
+    for (let round = 0; round < 3; round++) {
+      const start = performance.now();
+      scores = pairs.map(([a, b]) => similarityScore(a, b));
+      fastest = Math.min(fastest, performance.now() - start);
+    }
+    console.log(
+      "min_shared_words " +
+        minSharedWords +
+        ": " +
+        pairs.length +
+        " title pairs in " +
+        fastest.toFixed(0) +
+        " ms"
+    );
+    return scores;
+  };
+  const unfiltered = score(0);
+  // with the filter off, try it at two shared words
+  const filtered = score(configured > 0 ? configured : 2);
+  config.min_shared_words = configured;
+  // a changed score only matters if it decides a match
+  const threshold = Math.min(
+    ...Object.values(config.sources).map((source) => source.threshold)
+  );
+  let changed = 0;
+  pairs.forEach(([a, b], i) => {
+    if (filtered[i] === unfiltered[i]) {
       return;
     }
+    changed++;
+    if (unfiltered[i] >= threshold) {
+      console.log(
+        "Similarity " +
+          unfiltered[i].toFixed(3) +
+          " became " +
+          filtered[i].toFixed(3) +
+          ":\n  " +
+          a +
+          "\n  " +
+          b
+      );
+    }
+  });
+  const highest = Math.max(
+    0,
+    ...unfiltered.filter((_, i) => filtered[i] !== unfiltered[i])
+  );
+  console.log(
+    changed +
+      " scores changed, the highest was " +
+      highest.toFixed(3) +
+      " (the lowest threshold of the sources is " +
+      threshold +
+      ")"
+  );
+  process.exit(0);
+}
+
+if (buildIndex) {
+  writeIndex(entries);
+  process.exit(stats.errors > 0 ? 1 : 0);
+}
+
+if (buildAggregates) {
+  // Spellings of a name that only differ in case or punctuation are one
+  // author, listed under the spelling of the first paper (by file name).
+  let authors = new Map();
+  let venues = new Map();
+  const add = (map, key, name, id) => {
+    if (!map.has(key)) {
+      map.set(key, { name, papers: [] });
+    }
+    const papers = map.get(key).papers;
+    if (!papers.includes(id)) {
+      papers.push(id);
+    }
+  };
+  entries
+    .slice()
+    .sort((a, b) => a.file.localeCompare(b.file))
+    .forEach(({ file, paper }) => {
+      const id = file.replace(/\.yml$/, "");
+      (typeof paper.authors === "string" ? paper.authors.split(",") : [])
+        .map((name) => name.trim())
+        .filter((name) => name !== "")
+        .forEach((name) => add(authors, lastNameKey(name), name, id));
+      paper.publications.forEach((pub) => {
+        const venue = canonicalizeVenue(pub.name);
+        add(venues, venue, venue, id);
+      });
+    });
+  const write = (file, map) => {
+    let aggregate = {};
+    [...map.values()]
+      .sort((a, b) => a.name.localeCompare(b.name))
+      .forEach(({ name, papers }) => {
+        aggregate[name] = papers;
+      });
+    fs.writeFileSync(file, JSON.stringify(aggregate));
+    console.log("Wrote " + map.size + " entries to " + file);
+  };
+  write(authorsFile, authors);
+  write(venuesFile, venues);
+  process.exit(stats.errors > 0 ? 1 : 0);
+}
 
-    let title = hit.info.title;
-    if (fastls.get(title, paper.title) < 5) {
-      if (!("authors" in paper)) {
-        paper.authors = hit.info.authors.author
-          .map((a) => a.split(" ").at(-1))
-          .join(", ");
-        console.log(
-          "Setting authors of " + paper.title + " to " + paper.authors
-        );
+if (buildBibtex) {
+  // Papers without stored bibtex are cited by the name of their file, and
+  // papers sharing a DBLP record get distinct keys.
+  let used = new Map();
+  const bibtex = entries
+    .slice()
+    .sort((a, b) => a.file.localeCompare(b.file))
+    .map(({ file, paper }) => {
+      const text = paperBibtex(paper, file.replace(/\.yml$/, ""));
+      const key = parseBibtex(text).key;
+      const n = (used.get(key) || 0) + 1;
+      used.set(key, n);
+      if (n === 1) {
+        return text;
       }
+      log.warn("Renamed the bibtex key " + key + " of " + file + " (taken)");
+      return cleanBibtex(text, key + "-" + n);
+    });
+  fs.writeFileSync(bibtexFile, bibtex.join("\n\n") + "\n");
+  console.log("Wrote " + bibtex.length + " bibtex entries to " + bibtexFile);
+  process.exit(stats.errors > 0 ? 1 : 0);
+}
 
-      const venue = hit.info.venue;
-      if (!paper.publications.some((pub) => pub.name === venue)) {
-        console.log("Added publication at " + venue + " to " + paper.title);
-        paper.publications.push({
-          name: venue,
-          year: hit.info.year,
-          url: hit.info.ee,
-        });
-      }
-      //else {
-      //     let publ_index = paper.publications.findIndex(
-      //         (pub) => pub.name === venue
-      //     );
-      //     paper.publications[publ_index] = {
-      //         ...paper.publications[publ_index],
-      //         year: hit.info.year,
-      //       };
-      //   }
-    }
+const escapeXml = (text) =>
+  String(text)
+    .replace(/&/g, "&amp;")
+    .replace(/</g, "&lt;")
+    .replace(/>/g, "&gt;")
+    .replace(/"/g, "&quot;");
+const pubDate = (pub) =>
+  new Date(Date.UTC(pub.year, pub.month || 0, pub.day || 1));
+// a paper is as recent as its latest publication
+const latest = (paper) =>
+  paper.publications.reduce((a, b) => (pubDate(b) > pubDate(a) ? b : a));
+
+if (buildSitemap) {
+  // the list of all papers, then the page of each paper by its id (the name
+  // of its file), each dated by the latest publication it shows
+  const pages = entries
+    .slice()
+    .sort((a, b) => a.file.localeCompare(b.file))
+    .map(({ file, paper }) => ({
+      url: siteUrl + "papers/" + file.replace(/\.yml$/, "") + "/",
+      lastmod: pubDate(latest(paper)),
+    }));
+  pages.unshift({
+    url: siteUrl,
+    lastmod: pages
+      .map(({ lastmod }) => lastmod)
+      .reduce((a, b) => (b > a ? b : a), new Date(0)),
   });
+  const urls = pages.map(({ url, lastmod }) =>
+    [
+      "  <url>",
+      "    <loc>" + escapeXml(url) + "</loc>",
+      "    <lastmod>" + lastmod.toISOString().slice(0, 10) + "</lastmod>",
+      "  </url>",
+    ].join("\n")
+  );
+  fs.writeFileSync(
+    sitemapFile,
+    [
+      '<?xml version="1.0" encoding="utf-8"?>',
+      '<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">',
+      ...urls,
+      "</urlset>",
+    ].join("\n") + "\n"
+  );
+  console.log(
+    "Wrote the website and " + entries.length + " papers to " + sitemapFile
+  );
+  process.exit(stats.errors > 0 ? 1 : 0);
 }
 
-let updated = await Promise.all(
-  papers.map(async (file) => {
-    let paper = yaml.load(
-      fs.readFileSync(paper_dir + "/" + file, { encoding: "utf-8" })
-    );
+if (buildFeed) {
+  const recent = entries
+    .map(({ file, paper }) => ({ file, paper, pub: latest(paper) }))
+    .sort((a, b) => pubDate(b.pub) - pubDate(a.pub))
+    .slice(0, feedSize);
 
-    if (!("publications" in paper)) {
-      paper.publications = [];
+  const lines = [
+    '<?xml version="1.0" encoding="utf-8"?>',
+    '<feed xmlns="http://www.w3.org/2005/Atom">',
+    "  <title>ALPS - Algorithms with Predictions</title>",
+    "  <id>" + siteUrl + "</id>",
+    '  <link href="' + siteUrl + '"/>',
+    "  <updated>" +
+      (recent.length > 0 ? pubDate(recent[0].pub) : new Date()).toISOString() +
+      "</updated>",
+  ];
+  recent.forEach(({ file, paper, pub }) => {
+    lines.push("  <entry>");
+    lines.push("    <title>" + escapeXml(paper.title) + "</title>");
+    lines.push(
+      "    <id>" + siteUrl + "#" + file.replace(/\.yml$/, "") + "</id>"
+    );
+    if (pub.url) {
+      lines.push('    <link href="' + escapeXml(pub.url) + '"/>');
     }
+    lines.push("    <updated>" + pubDate(pub).toISOString() + "</updated>");
+    (paper.authors || "")
+      .split(",")
+      .map((name) => name.trim())
+      .filter((name) => name !== "")
+      .forEach((name) =>
+        lines.push("    <author><name>" + escapeXml(name) + "</name></author>")
+      );
+    lines.push(
+      "    <summary>" + escapeXml(pub.name + " " + pub.year) + "</summary>"
+    );
+    lines.push("  </entry>");
+  });
+  lines.push("</feed>");
+  fs.writeFileSync(feedFile, lines.join("\n") + "\n");
+  console.log("Wrote " + recent.length + " papers to " + feedFile);
+  process.exit(stats.errors > 0 ? 1 : 0);
+}
+
+if (coverage) {
+  const peerReviewed = (paper) =>
+    paper.publications.filter((pub) => !isPreprint(pub));
+  const missing = (pubs, field) => pubs.filter((pub) => !(field in pub)).length;
+  const rows = entries.map(({ file, paper }) => {
+    const published = peerReviewed(paper);
+    return {
+      file,
+      missingAuthors: !("authors" in paper) ? 1 : 0,
+      noPeerReviewed: published.length === 0 ? 1 : 0,
+      noLabels: paper.labels.length === 0 ? 1 : 0,
+      withoutBibtex: missing(published, "bibtex"),
+      withoutUrl: missing(paper.publications, "url"),
+      withoutDoi: missing(published, "doi"),
+      withdrawn: paper.withdrawn === true ? 1 : 0,
+    };
+  });
+  const columns = [
+    ["missingAuthors", "papers without authors"],
+    ["noPeerReviewed", "papers without a peer-reviewed publication"],
+    ["noLabels", "papers without labels"],
+    ["withoutBibtex", "peer-reviewed publications without bibtex"],
+    ["withoutUrl", "publications without url"],
+    ["withoutDoi", "peer-reviewed publications without DOI"],
+    ["withdrawn", "withdrawn or retracted papers"],
+  ];
+  columns.forEach(([key, description]) => {
+    const total = rows.reduce((sum, row) => sum + row[key], 0);
+    console.log(String(total).padStart(5) + " " + description);
+  });
+
+  // papers with the most gaps first
+  const gaps = (row) => columns.reduce((sum, [key]) => sum + row[key], 0);
+  const csv = rows
+    .filter((row) => gaps(row) > 0)
+    .sort((a, b) => gaps(b) - gaps(a) || a.file.localeCompare(b.file))
+    .map((row) => [row.file, ...columns.map(([key]) => row[key])].join(","));
+  fs.writeFileSync(
+    coverageFile,
+    ["file", ...columns.map(([key]) => key)].join(",") +
+      "\n" +
+      csv.map((line) => line + "\n").join("")
+  );
+  console.log(
+    "Wrote " + csv.length + " of " + rows.length + " papers to " + coverageFile
+  );
+  process.exit(stats.errors > 0 ? 1 : 0);
+}
+
+if (exportCsv) {
+  // quoted if needed, with doubled quotes, as spreadsheets expect
+  const csvField = (value) => {
+    const text = value === undefined ? "" : String(value);
+    return /[",\r\n]/.test(text) ? '"' + text.replace(/"/g, '""') + '"' : text;
+  };
+  const rows = entries
+    .slice()
+    .sort((a, b) => a.file.localeCompare(b.file))
+    .map(({ paper }) => {
+      // the venue the bibtex export cites
+      const pub =
+        paper.publications.find((pub) => !isPreprint(pub)) ||
+        paper.publications[0];
+      const preprint = paper.publications.find(
+        (pub) => pub.name === "arXiv" && typeof pub.url === "string"
+      );
+      return [
+        paper.title,
+        paper.authors,
+        paper.labels.join("; "),
+        pub.name,
+        pub.year,
+        preprint === undefined ? undefined : preprint.url,
+      ]
+        .map(csvField)
+        .join(",");
+    });
+  fs.writeFileSync(
+    csvFile,
+    "title,authors,labels,venue,year,arxiv_url\n" +
+      rows.map((row) => row + "\n").join("")
+  );
+  console.log("Wrote " + rows.length + " papers to " + csvFile);
+  process.exit(stats.errors > 0 ? 1 : 0);
+}
 
+if (verify) {
+  let found = new Map();
+  await mapConcurrent(entries, concurrency, async ({ file, paper }) => {
     try {
-      await updateFromArxiv(paper);
+      found.set(file, await verifyPaper(paper));
     } catch (error) {
+      countError(error instanceof UpdaterError ? error.kind : "internal");
+      log.error(
+        "Failed to verify " +
+          paper_dir +
+          "/" +
+          file +
+          " (" +
+          (error.response
+            ? "HTTP " + error.response.status
+            : error.code || error.message) +
+          ")"
+      );
+    }
+  });
+  let count = 0;
+  [...found.keys()].sort().forEach((file) => {
+    found.get(file).forEach(({ source, field, stored, fetched }) => {
+      count++;
+      console.log("--- " + paper_dir + "/" + file);
+      console.log("+++ " + source);
+      // the venue of a publication is its name
+      const key = field === "venue" ? stored + " name" : field;
+      const record = (provenance[path.join(paper_dir, file)] || {})[key];
       console.log(
-        "Failed to fetch data from arXiv for the paper: " + paper.title
+        record === undefined
+          ? "@@ not set by the updater @@"
+          : "@@ set from " + record.source + " on " + record.date + " @@"
       );
+      console.log("-" + field + ": " + stored);
+      console.log("+" + field + ": " + fetched);
+    });
+  });
+  console.log(
+    "Found " +
+      count +
+      " discrepancies in " +
+      found.size +
+      " papers, " +
+      errorSummary()
+  );
+  process.exit(stats.errors > 0 || count > 0 ? 1 : 0);
+}
+
+if (checkLinks) {
+  const links = entries.flatMap(({ file, paper }) =>
+    paper.publications
+      .filter((pub) => typeof pub.url === "string")
+      .map((pub) => ({ file, url: pub.url }))
+  );
+  const results = await mapConcurrent(links, concurrency, (link) =>
+    checkLink(link.url)
+  );
+
+  // only links that fail or redirect are listed, with their paper file
+  let broken = 0;
+  let suggestions = [];
+  const width = Math.max(4, ...links.map(({ file }) => file.length)) + 1;
+  const indent = " ".repeat(12 + width);
+  console.log("status hops " + "file".padEnd(width) + "url");
+  links.forEach((link, i) => {
+    const result = results[i];
+    const failed = result.error !== undefined || result.status >= 400;
+    if (!failed && result.chain.length === 0) {
+      return;
     }
+    if (failed) {
+      broken++;
+    }
+    console.log(
+      String(result.error !== undefined ? "error" : result.status).padEnd(7) +
+        String(result.chain.length).padEnd(5) +
+        link.file.padEnd(width) +
+        link.url
+    );
+    if (result.finalUrl !== link.url) {
+      console.log(indent + "-> " + result.finalUrl);
+    }
+    if (result.error !== undefined) {
+      console.log(indent + "(" + result.error + ")");
+    }
+    // temporary redirects (like those of doi.org) are no reason to change
+    // the url
+    const moved = result.chain.every(({ status }) =>
+      [301, 308].includes(status)
+    );
+    if (!failed && result.chain.length > 0 && moved) {
+      suggestions.push(
+        paper_dir + "/" + link.file + ": " + link.url + " -> " + result.finalUrl
+      );
+    }
+  });
+  if (suggestions.length > 0) {
+    console.log("Links that moved permanently, consider updating them:");
+    suggestions.forEach((suggestion) => console.log("  " + suggestion));
+  }
+  console.log("Checked " + links.length + " links, " + broken + " broken");
+  process.exit(broken > 0 ? 1 : 0);
+}
+
+if (suggestLabelsMode) {
+  await mapConcurrent(entries, concurrency, async ({ file, paper }) => {
+    let abstract = paper.abstract || "";
+    let categories = [];
     try {
-      await updateFromDBLP(paper);
+      const [match] = rankMatches(
+        paper,
+        await searchArxiv(paper),
+        (hit) => ({ title: hit.title, venue: "arXiv" }),
+        config.sources.arxiv.threshold
+      );
+      if (match !== undefined) {
+        const hit = match.hit;
+        abstract = abstract || hit.summary;
+        categories = [hit.category].flat().map((c) => c["@_term"]);
+      }
     } catch (error) {
-      console.log(
-        "Failed to fetch data from DBLP for the paper: " + paper.title
+      stats.errors++;
+      log.error(
+        "Failed to fetch data from arXiv for the paper: " + paper.title
       );
     }
-    return [file, paper];
-  })
-);
 
-updated.forEach(([file, paper]) =>
-  fs.writeFileSync("papers/" + file, yaml.dump(paper, { lineWidth: -1 }))
+    const suggested = suggestLabels(paper, abstract, categories);
+    if (suggested.length > 0) {
+      console.log(paper_dir + "/" + file + ": " + suggested.join(", "));
+    }
+  });
+  process.exit(stats.errors > 0 ? 1 : 0);
+}
+
+if (discover) {
+  const query = config.discover.terms
+    .map((term) => "all:" + encodeURIComponent('"' + term + '"'))
+    .join("+OR+");
+  let candidates = [];
+  try {
+    let info = await get(
+      "http://export.arxiv.org/api/query?sortBy=submittedDate" +
+        "&sortOrder=descending&max_results=" +
+        config.discover.max_results +
+        "&search_query=" +
+        query
+    );
+    candidates = parseArxivFeed(info.data);
+  } catch (error) {
+    log.error("Failed to search arXiv for new papers: " + error.message);
+    process.exit(1);
+  }
+
+  const missing = candidates.filter(
+    (hit) =>
+      !entries.some(
+        ({ paper }) =>
+          similarityScore(hit.title, paper.title) >=
+          config.sources.arxiv.threshold
+      )
+  );
+  // the search terms also match preprints that merely mention them
+  const relevant = missing
+    .map((hit) => ({
+      hit,
+      ...relevance(hit.title + " " + (hit.summary || "")),
+    }))
+    .filter((candidate) => candidate.relevant);
+  relevant.forEach(({ hit, score, phrases }) => {
+    console.log(hit.title.replace(/\s+/g, " "));
+    console.log(
+      "  " +
+        [hit.author]
+          .flat()
+          .map((a) => a.name)
+          .join(", ")
+    );
+    console.log(
+      "  " + hit.id.replace(/v\d+$/, "") + " " + hit.published.slice(0, 10)
+    );
+    console.log("  relevance " + score + ": " + phrases.join(", "));
+  });
+  console.log(
+    "Found " +
+      relevant.length +
+      " of " +
+      candidates.length +
+      " recent preprints not in the collection (" +
+      (missing.length - relevant.length) +
+      " more below the relevance threshold)"
+  );
+  process.exit(0);
+}
+
+if (!noDupCheck) {
+  for (let i = 0; i < entries.length; i++) {
+    for (let j = i + 1; j < entries.length; j++) {
+      const score = similarityScore(
+        entries[i].paper.title,
+        entries[j].paper.title
+      );
+      if (score > dupThreshold) {
+        log.warn(
+          "Probable duplicate (similarity " +
+            score.toFixed(2) +
+            "): " +
+            paper_dir +
+            "/" +
+            entries[i].file +
+            " and " +
+            paper_dir +
+            "/" +
+            entries[j].file
+        );
+      }
+    }
+  }
+
+  // Papers with a publication of the same DOI or DBLP record are the same
+  // work, whatever their titles.
+  let owners = new Map();
+  entries.forEach(({ file, paper }) =>
+    paper.publications.forEach((pub) => {
+      const ids = [
+        typeof pub.doi === "string" && "DOI " + pub.doi.toLowerCase(),
+        typeof pub.dblp_key === "string" && "DBLP key " + pub.dblp_key,
+      ];
+      ids
+        .filter((id) => id)
+        .forEach((id) => owners.set(id, [...(owners.get(id) || []), file]));
+    })
+  );
+  owners.forEach((files, identifier) => {
+    const distinct = [...new Set(files)];
+    if (distinct.length > 1) {
+      stats.duplicates.push({ identifier, files: distinct });
+      log.warn(
+        "Papers share the " +
+          identifier +
+          ": " +
+          distinct.map((file) => paper_dir + "/" + file).join(", ")
+      );
+    }
+  });
+}
+
+// --only-missing-bibtex only asks DBLP for bibtex
+const sources = onlyMissingBibtex ? [bibtexSource] : enabledSources();
+allSources
+  .filter((source) => !onlyMissingBibtex && config.sources[source.key].enabled)
+  .forEach((source) => {
+    const missing = missingCredentials(source.key);
+    if (missing.length > 0) {
+      log.warn(missing.join(" and ") + " not set, skipping " + source.name);
+    }
+  });
+
+sources.forEach((source) => {
+  stats.sources[source.name] = { matched: 0, unmatched: 0, failed: 0 };
+});
+
+if (interactive && !process.stdin.isTTY) {
+  log.error("--interactive needs a terminal to ask for confirmations");
+  process.exit(1);
+}
+
+// the paper directory relative to the top of the repository, as git show
+// names the committed files
+let committedDir;
+if (diffAgainst !== undefined) {
+  try {
+    execFileSync("git", ["rev-parse", "--verify", diffAgainst + "^{commit}"], {
+      stdio: "ignore",
+    });
+  } catch (error) {
+    log.error("--diff-against needs a git commit, not " + diffAgainst);
+    process.exit(1);
+  }
+  const top = execFileSync("git", ["rev-parse", "--show-toplevel"], {
+    encoding: "utf-8",
+  }).trim();
+  committedDir = path.relative(
+    fs.realpathSync(top),
+    fs.realpathSync(paper_dir)
+  );
+  if (committedDir.startsWith("..") || path.isAbsolute(committedDir)) {
+    log.error(
+      "--diff-against needs the papers in the repository at " +
+        top +
+        ", not in " +
+        paper_dir
+    );
+    process.exit(1);
+  }
+}
+
+// all valid papers, of which the following selects those to update
+const allEntries = entries;
+
+if (paperPattern !== undefined) {
+  entries = entries.filter(({ file }) => selected(file));
+  if (entries.length === 0) {
+    log.error("No valid paper file matches " + paperPattern);
+    process.exit(1);
+  }
+}
+
+let state = loadState();
+if (onlyMissingBibtex) {
+  const before = entries.length;
+  entries = entries.filter(({ paper }) => missingBibtex(paper).length > 0);
+  stats.skipped += before - entries.length;
+}
+
+if (onlyNew) {
+  entries = entries.filter(({ file, original, paper }) => {
+    const known = state.papers[file];
+    const skip =
+      hasCompleteMetadata(paper) &&
+      known !== undefined &&
+      known.hash === paperHash(paper);
+    if (skip) {
+      stats.skipped++;
+    }
+    return !skip;
+  });
+}
+
+// Runs over all papers keep a journal, other runs neither skip nor record
+// papers in it.
+const useJournal =
+  paperPattern === undefined &&
+  !dryRun &&
+  !statsOnly &&
+  diffAgainst === undefined;
+if (useJournal && fs.existsSync(journalFile)) {
+  const done = new Set(
+    fs
+      .readFileSync(journalFile, { encoding: "utf-8" })
+      .split("\n")
+      .filter((line) => line !== "")
+  );
+  const before = entries.length;
+  entries = entries.filter(({ file }) => !done.has(file));
+  stats.skipped += before - entries.length;
+  log.info(
+    "Resuming an interrupted run, skipping " +
+      (before - entries.length) +
+      " papers it already saved"
+  );
+}
+
+// Writes the paper file if the run changed it (or prints the diff in a dry
+// run), backing up the old file unless `backup` is false, and records it in
+// the state.
+function savePaper(file, paper, original, backup = true) {
+  const hash = paperHash(paper);
+  // files the run did not change keep their formatting, and get no backup
+  if (hash !== paperHash(yaml.load(original))) {
+    const content = formatPaper(paper);
+    if (!dryRun) {
+      if (backup) {
+        backupFile(file);
+      }
+      writePaper(file, content);
+    } else {
+      withoutStatus(() =>
+        console.log(unifiedDiff(paper_dir + "/" + file, original, content))
+      );
+    }
+  }
+  state.papers[file] = {
+    processedAt: new Date().toISOString(),
+    hash,
+  };
+}
+
+// Records in the journal that the run is done with the paper file.
+function journalPaper(file) {
+  if (useJournal) {
+    fs.appendFileSync(journalFile, file + "\n");
+  }
+}
+
+// The paper file at the --diff-against ref, or undefined if it did not exist
+// there.
+function committedPaper(file) {
+  try {
+    return yaml.load(
+      execFileSync(
+        "git",
+        [
+          "show",
+          diffAgainst +
+            ":" +
+            path.posix.join(...committedDir.split(path.sep), file),
+        ],
+        { encoding: "utf-8", stdio: ["ignore", "pipe", "ignore"] }
+      )
+    );
+  } catch (error) {
+    return undefined;
+  }
+}
+
+// The fields (named as by fieldValues) the update added (+), removed (-) or
+// changed (~), compared with the committed paper.
+function fieldChanges(committed, paper) {
+  const before = committed === undefined ? new Map() : fieldValues(committed);
+  const after = fieldValues(paper);
+  let changes = [];
+  after.forEach((value, field) => {
+    if (!before.has(field)) {
+      changes.push("+ " + field + ": " + value);
+    } else if (before.get(field) !== value) {
+      changes.push("~ " + field + ": " + before.get(field) + " -> " + value);
+    }
+  });
+  before.forEach((value, field) => {
+    if (!after.has(field)) {
+      changes.push("- " + field + ": " + value);
+    }
+  });
+  return changes;
+}
+
+// Minutes and seconds like 3:07.
+const duration = (ms) => {
+  const seconds = Math.round(ms / 1000);
+  return Math.floor(seconds / 60) + ":" + String(seconds % 60).padStart(2, "0");
+};
+
+// Shows how many papers are done, the elapsed and the estimated remaining
+// time and the paper started last, unless --quiet (or the prompts of
+// --interactive need the terminal).
+const started = Date.now();
+// fixed before the run, whatever happens to the list of entries meanwhile
+const total = entries.length;
+let done = 0;
+let current = "";
+const showProgress = () => {
+  if (quiet || interactive) {
+    return;
+  }
+  const elapsed = Date.now() - started;
+  const eta =
+    done === 0 ? "?" : duration((elapsed / done) * (total - done));
+  setStatus(
+    "[" +
+      done +
+      "/" +
+      total +
+      "] " +
+      duration(elapsed) +
+      " elapsed, ETA " +
+      eta +
+      " " +
+      current
+  );
+};
+
+// The best similarity and whether there was a match, per paper, for
+// --stats-only.
+let analyzed = [];
+// The changed fields per paper, for --diff-against.
+let changed = [];
+// The entries of the saved papers whose bibtex is still to be fetched.
+let awaitingBibtex = [];
+await mapConcurrent(entries, concurrency, async (entry) => {
+  const { file, original, paper } = entry;
+  if (Date.now() > deadline) {
+    stats.remaining++;
+    return;
+  }
+  current = paper.title;
+  showProgress();
+  const result = await updatePaper(paper, sources);
+  done++;
+  showProgress();
+  Object.entries(result.sources).forEach(([name, outcome]) => {
+    stats.sources[name][outcome]++;
+  });
+  result.failures.forEach(({ source, reason, kind }) => {
+    countError(kind);
+    stats.failed.push({ file, reason: source + ": " + reason, kind });
+  });
+  stats.mergedPublications += result.mergedPublications;
+  result.mismatches.forEach((reason) =>
+    stats.mismatches.push({ file, reason })
+  );
+  result.attention.forEach((reason) => stats.attention.push({ file, reason }));
+  stats.papers++;
+  const unmatchedSources = Object.keys(result.sources).filter(
+    (name) => result.sources[name] === "unmatched"
+  );
+  if (
+    unmatchedSources.length > 0 &&
+    !Object.values(result.sources).includes("matched")
+  ) {
+    const scores = {};
+    unmatchedSources.forEach((name) => {
+      scores[name] =
+        result.scores[name] === undefined ? null : result.scores[name];
+    });
+    stats.noMatch.push({ file, scores });
+  }
+  if (statsOnly) {
+    const scores = Object.values(result.scores);
+    analyzed.push({
+      file,
+      best: scores.length > 0 ? Math.max(...scores) : undefined,
+      matched: Object.values(result.sources).includes("matched"),
+    });
+  } else if (diffAgainst !== undefined) {
+    const changes = fieldChanges(committedPaper(file), paper);
+    if (changes.length > 0) {
+      changed.push({ file, changes });
+    }
+  } else {
+    savePaper(file, paper, original);
+    // a paper is done once its bibtex is there, so that a run interrupted
+    // before that fetches the bibtex again
+    if (hasPendingBibtex(paper)) {
+      awaitingBibtex.push(entry);
+    } else {
+      journalPaper(file);
+    }
+    if (!dryRun && Object.keys(result.provenance).length > 0) {
+      const date = new Date().toISOString().slice(0, 10);
+      const fields = provenanceOf(file);
+      Object.entries(result.provenance).forEach(([field, source]) => {
+        fields[field] = { source, date };
+      });
+    }
+  }
+});
+
+closePrompt();
+setStatus("");
+// the progress line counted every paper once against the fixed total
+assert.strictEqual(done + stats.remaining, total);
+
+// the bibtex of the DBLP matches, see deferBibtex
+const withBibtex = await fetchPendingBibtex(concurrency);
+const fetchedOn = new Date().toISOString().slice(0, 10);
+withBibtex.forEach(({ paper, pub }) => {
+  const { file } = entries.find((entry) => entry.paper === paper);
+  provenanceOf(file)[pub.name + " bibtex"] = {
+    source: "DBLP",
+    date: fetchedOn,
+  };
+});
+awaitingBibtex.forEach(({ file, paper, original }) => {
+  if (withBibtex.some((added) => added.paper === paper)) {
+    // checked and sorted as updatePaper does with the bibtex it fetches
+    const reported = stats.mismatches
+      .filter((mismatch) => mismatch.file === file)
+      .map(({ reason }) => reason);
+    checkBibtex(paper, config.sources.dblp.threshold)
+      .filter((reason) => !reported.includes(reason))
+      .forEach((reason) => {
+        log.warn(paper.title + ": " + reason);
+        stats.mismatches.push({ file, reason });
+      });
+    sortPublications(paper);
+    // the first save kept the backup of the file from before the run
+    savePaper(file, paper, original, false);
+  }
+  journalPaper(file);
+});
+
+if (diffAgainst !== undefined) {
+  changed
+    .sort((a, b) => a.file.localeCompare(b.file))
+    .forEach(({ file, changes }) => {
+      console.log(paper_dir + "/" + file);
+      changes.forEach((line) => console.log("  " + line));
+    });
+  console.log(
+    changed.length +
+      " of " +
+      stats.papers +
+      " papers differ from " +
+      diffAgainst +
+      " after the update"
+  );
+  process.exit(stats.errors > 0 ? 1 : 0);
+}
+
+if (statsOnly) {
+  // buckets of width 0.05 by their lower end, exact matches separately
+  let buckets = new Map();
+  analyzed.forEach(({ best }) => {
+    const bucket =
+      best === undefined
+        ? "no results"
+        : best === 1
+        ? "1.00"
+        : (Math.floor(best * 20) / 20).toFixed(2) +
+          "-" +
+          ((Math.floor(best * 20) + 1) / 20).toFixed(2);
+    buckets.set(bucket, (buckets.get(bucket) || 0) + 1);
+  });
+  console.log("Best title similarity per paper:");
+  [...buckets.keys()].sort().forEach((bucket) => {
+    const count = buckets.get(bucket);
+    console.log(
+      "  " +
+        bucket.padEnd(10) +
+        String(count).padStart(5) +
+        " " +
+        "#".repeat(Math.ceil((50 * count) / analyzed.length))
+    );
+  });
+  const unmatched = analyzed.filter(({ matched }) => !matched);
+  console.log(unmatched.length + " papers without a match in any source:");
+  unmatched
+    .sort((a, b) => a.file.localeCompare(b.file))
+    .forEach(({ file, best }) =>
+      console.log(
+        "  " +
+          paper_dir +
+          "/" +
+          file +
+          (best === undefined ? "" : " (best " + best.toFixed(3) + ")")
+      )
+    );
+  process.exit(stats.errors > 0 ? 1 : 0);
+}
+
+if (!dryRun) {
+  fs.writeFileSync(stateFile, JSON.stringify(state, null, 2) + "\n");
+  // records of removed files of the paper directory are dropped, those of
+  // other directories are left alone
+  const removed = (key) =>
+    key === path.join(paper_dir, path.basename(key)) &&
+    !papers.includes(path.basename(key));
+  const sorted = {};
+  Object.keys(provenance)
+    .filter((key) => !removed(key) && Object.keys(provenance[key]).length > 0)
+    .sort()
+    .forEach((key) => (sorted[key] = provenance[key]));
+  if (Object.keys(sorted).length > 0) {
+    fs.writeFileSync(provenanceFile, JSON.stringify(sorted, null, 2) + "\n");
+  }
+}
+// the journal lets the next run continue with the remaining papers
+if (useJournal && stats.remaining === 0) {
+  fs.rmSync(journalFile, { force: true });
+}
+
+console.log(
+  "Processed " +
+    stats.papers +
+    " papers, " +
+    errorSummary() +
+    ", " +
+    stats.mergedPublications +
+    " merged publications, " +
+    stats.skipped +
+    " skipped papers" +
+    (stats.remaining > 0
+      ? ", " + stats.remaining + " papers left after the deadline"
+      : "")
 );
+if (stats.noMatch.length > 0) {
+  console.log(stats.noMatch.length + " papers without a match in any source:");
+  stats.noMatch
+    .sort((a, b) => a.file.localeCompare(b.file))
+    .forEach(({ file, scores }) =>
+      console.log(
+        "  " +
+          paper_dir +
+          "/" +
+          file +
+          " (" +
+          Object.entries(scores)
+            .map(
+              ([name, score]) =>
+                name + " " + (score === null ? "no results" : score.toFixed(3))
+            )
+            .join(", ") +
+          ")"
+      )
+    );
+}
+fs.writeFileSync(reportFile, JSON.stringify(statsReport(), null, 2) + "\n");
+// the updated papers are the saved ones, except in a dry run
+if (buildAfterUpdate && !dryRun) {
+  writeIndex(allEntries);
+}
+if (stats.errors > maxErrors) {
+  console.error(
+    stats.errors + " errors, more than the " + maxErrors + " allowed"
+  );
+  process.exitCode = 1;
+}
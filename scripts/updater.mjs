@@ -0,0 +1,3116 @@
+// Fetches and merges the metadata of papers from the sources, see updatePaper.
+// updateData.mjs is the command line interface on top of it.
+
+import axios from "axios";
+import crypto from "crypto";
+import fs from "fs";
+import yaml from "js-yaml";
+import { XMLParser } from "fast-xml-parser";
+import fastls from "fast-levenshtein";
+import path from "path";
+import readline from "readline";
+import { fileURLToPath } from "url";
+
+// The configuration files are found next to this module, wherever it is used
+// from.
+const scriptDir = path.dirname(fileURLToPath(import.meta.url));
+const configFile = path.join(scriptDir, "updater.yml");
+// Answers given in interactive mode, by paper title and candidate.
+const decisionsFile = path.join(scriptDir, "decisions.json");
+
+// Settings of a run that are not part of updater.yml, see configure.
+export const settings = {
+  // directory of the response cache
+  cacheDir: ".updater-cache",
+  // bypass the cache, but still refresh it with the fetched responses
+  noCache: false,
+  // maximal age of a cached response in days
+  cacheTtl: 7,
+  // number of retries of requests that failed with a transient error
+  maxRetries: 3,
+  // answer all requests from the responses in this directory instead of the
+  // network, for deterministic runs
+  fixturesDir: undefined,
+  // ask before applying matches whose similarity is in the uncertain band
+  interactive: false,
+  // leave the bibtex of DBLP matches to fetchPendingBibtex, so that the
+  // matching of all papers is not held up by the bibtex requests
+  deferBibtex: false,
+  // messages below this level (debug, info, warn or error) are not printed,
+  // "none" silences all
+  logLevel: process.env.LOG_LEVEL || "info",
+};
+
+// Changes the given settings for all following calls.
+export function configure(changes) {
+  Object.assign(settings, changes);
+}
+
+// The label vocabulary, see labels.yml.
+const labelVocabulary = Object.entries(
+  yaml.load(
+    fs.readFileSync(path.join(scriptDir, "labels.yml"), { encoding: "utf-8" })
+  )
+).map(([label, rules]) => ({
+  label,
+  keywords: (rules && rules.keywords) || [],
+  categories: (rules && rules.categories) || [],
+}));
+
+// Maps the normalized aliases of a venue to its canonical name.
+const venueAliases = new Map();
+Object.entries(
+  yaml.load(
+    fs.readFileSync(path.join(scriptDir, "venue_aliases.yml"), {
+      encoding: "utf-8",
+    })
+  )
+).forEach(([canonical, aliases]) =>
+  [canonical, ...aliases].forEach((alias) =>
+    venueAliases.set(venueKey(alias), canonical)
+  )
+);
+
+// Maps the path of DBLP record keys like conf/soda to the venue name.
+const dblpVenues = new Map(
+  Object.entries(
+    yaml.load(
+      fs.readFileSync(path.join(scriptDir, "dblp_venues.yml"), {
+        encoding: "utf-8",
+      })
+    )
+  )
+);
+
+// The phrases that make a preprint relevant to --discover, with their
+// weights, and the relevance it needs, see relevance.yml.
+const relevanceRubric = yaml.load(
+  fs.readFileSync(path.join(scriptDir, "relevance.yml"), { encoding: "utf-8" })
+);
+
+// Settings that can be overridden in updater.yml. `threshold` is the minimal
+// title similarity of a search result to be considered the paper,
+// `rate_limit_ms` the average delay between two requests to the source and
+// the optional `burst` (default 1) the number of requests that may be sent
+// without delay after a pause.
+const defaultConfig = {
+  // number of papers that are processed at the same time
+  concurrency: 4,
+  // number of search results requested from each source
+  max_results: 10,
+  // with --interactive, matches with a similarity of at least `min` and
+  // below `max` are only applied after confirmation
+  uncertain: { min: 0.8, max: 0.95 },
+  // search terms and number of recent arXiv submissions checked by
+  // --discover
+  discover: {
+    terms: ["learning-augmented", "algorithms with predictions"],
+    max_results: 100,
+  },
+  // how the authors string of new papers is written: "last" (Last),
+  // "initial_last" (F. Last) or "full" (First Last)
+  author_format: "last",
+  // whether inline math in abstracts from arXiv is kept as LaTeX ("keep")
+  // or converted to unicode ("unicode")
+  latex_math: "keep",
+  // number of years a DBLP publication of each kind may precede the arXiv
+  // preprint before the match is reported
+  year_slack: { conference: 1, journal: 1 },
+  // whether preprints are listed before ("first") or after ("last") the
+  // peer-reviewed publications of a paper
+  preprints: "first",
+  // the month each conference takes place, like { SODA: jan }; DBLP
+  // publications at these venues without a month get this month, marked
+  // approximate_date, if the paper has a dated preprint
+  venue_months: {},
+  // how titles are compared, see similarityMethods
+  similarity: "levenshtein",
+  // titles sharing fewer words (or all words of the shorter title) have a
+  // similarity of 0 without comparing them further; 0 compares all titles,
+  // since typos can leave similar titles with few shared words
+  min_shared_words: 0,
+  sources: {
+    arxiv: { enabled: true, threshold: 0.9, rate_limit_ms: 3000 },
+    dblp: { enabled: true, threshold: 0.9, rate_limit_ms: 1000 },
+    crossref: { enabled: true, threshold: 0.9, rate_limit_ms: 200 },
+    openreview: { enabled: true, threshold: 0.9, rate_limit_ms: 1000 },
+    semantic_scholar: { enabled: true, threshold: 0.9, rate_limit_ms: 1000 },
+    openalex: { enabled: true, threshold: 0.9, rate_limit_ms: 200 },
+    // few papers of the collection appear in PubMed
+    pubmed: { enabled: false, threshold: 0.9, rate_limit_ms: 400 },
+  },
+  // contact address sent to OpenAlex, CrossRef and PubMed, which serve
+  // requests with one from their faster "polite pool"
+  mailto: undefined,
+  // API keys; Semantic Scholar is only queried with one, PubMed allows more
+  // requests with one
+  semantic_scholar_api_key: undefined,
+  ncbi_api_key: undefined,
+  // the User-Agent header of all requests; by default it names the updater,
+  // the website and the contact address
+  user_agent: undefined,
+  // address of the website, for the links of the feed and the sitemap
+  site_url: "https://algorithms-with-predictions.github.io/",
+};
+
+function loadConfig() {
+  if (!fs.existsSync(configFile)) {
+    return defaultConfig;
+  }
+  const custom =
+    yaml.load(fs.readFileSync(configFile, { encoding: "utf-8" })) || {};
+  let sources = {};
+  Object.entries(defaultConfig.sources).forEach(([name, settings]) => {
+    sources[name] = { ...settings, ...(custom.sources || {})[name] };
+  });
+  const uncertain = { ...defaultConfig.uncertain, ...custom.uncertain };
+  const discover = { ...defaultConfig.discover, ...custom.discover };
+  const year_slack = { ...defaultConfig.year_slack, ...custom.year_slack };
+  return {
+    ...defaultConfig,
+    ...custom,
+    uncertain,
+    discover,
+    year_slack,
+    sources,
+  };
+}
+
+export const config = loadConfig();
+
+// The API keys and the contact address, from the environment or else from
+// updater.yml, so that secrets need not be stored in a file.
+export const credentials = {
+  semanticScholarKey:
+    process.env.SEMANTIC_SCHOLAR_API_KEY || config.semantic_scholar_api_key,
+  ncbiKey: process.env.NCBI_API_KEY || config.ncbi_api_key,
+  mailto: process.env.OPENALEX_MAILTO || config.mailto,
+};
+
+// The credentials each source cannot be queried without, with the
+// environment variable that provides them. The shared rate limit of Semantic
+// Scholar without a key is too low for a full run.
+const requiredCredentials = {
+  semantic_scholar: [["semanticScholarKey", "SEMANTIC_SCHOLAR_API_KEY"]],
+};
+
+// The environment variables of the credentials the source lacks.
+export const missingCredentials = (key) =>
+  (requiredCredentials[key] || [])
+    .filter(([name]) => !credentials[name])
+    .map(([, variable]) => variable);
+
+// The query parameter with the contact address, if one is configured.
+const mailtoParameter = (name) =>
+  credentials.mailto
+    ? "&" + name + "=" + encodeURIComponent(credentials.mailto)
+    : "";
+
+const sourceHosts = {
+  arxiv: "export.arxiv.org",
+  dblp: "dblp.org",
+  crossref: "api.crossref.org",
+  openreview: "api2.openreview.net",
+  semantic_scholar: "api.semanticscholar.org",
+  openalex: "api.openalex.org",
+  pubmed: "eutils.ncbi.nlm.nih.gov",
+};
+// Minimal delay in milliseconds between two requests to the same host.
+const hostDelays = {};
+// Number of requests to the same host that may be sent at once.
+const hostBursts = {};
+Object.entries(sourceHosts).forEach(([source, host]) => {
+  hostDelays[host] = config.sources[source].rate_limit_ms;
+  hostBursts[host] = config.sources[source].burst || 1;
+});
+// A token bucket per host, see waitForHost.
+const hostBuckets = new Map();
+
+const logLevels = ["debug", "info", "warn", "error"];
+
+// A line like the progress of a run that stays below the log messages on the
+// terminal, empty for none.
+let status = "";
+
+const drawStatus = () =>
+  process.stderr.write(
+    "\r\x1b[K" + status.slice(0, (process.stderr.columns || 80) - 1)
+  );
+
+// Shows the text as the status line, replacing the previous one; does
+// nothing unless stderr is a terminal.
+export function setStatus(text) {
+  if (process.stderr.isTTY) {
+    status = text;
+    drawStatus();
+  }
+}
+
+// Prints with the given function above the status line.
+export function withoutStatus(print) {
+  if (status !== "") {
+    process.stderr.write("\r\x1b[K");
+  }
+  print();
+  if (status !== "") {
+    drawStatus();
+  }
+}
+
+function logAt(level, message) {
+  const i = logLevels.indexOf(level);
+  const minimum =
+    settings.logLevel === "none"
+      ? logLevels.length
+      : Math.max(0, logLevels.indexOf(settings.logLevel));
+  if (i < minimum) {
+    return;
+  }
+  withoutStatus(() => {
+    if (level === "warn" || level === "error") {
+      console.error(level + ": " + message);
+    } else {
+      console.log(message);
+    }
+  });
+}
+
+export const log = {
+  debug: (message) => logAt("debug", message),
+  info: (message) => logAt("info", message),
+  warn: (message) => logAt("warn", message),
+  error: (message) => logAt("error", message),
+};
+
+const sleep = (ms) => new Promise((resolve) => setTimeout(resolve, ms));
+
+// Resolves once the given host may be queried. Every request takes a token
+// from the host's bucket, which refills by one token per delay of the host up
+// to its burst size; requests of concurrently processed papers queue for the
+// tokens, while requests to different hosts do not wait for each other.
+function waitForHost(host) {
+  if (settings.fixturesDir !== undefined) {
+    return Promise.resolve();
+  }
+  const delay = hostDelays[host] || 0;
+  const burst = hostBursts[host] || 1;
+  if (!hostBuckets.has(host)) {
+    hostBuckets.set(host, {
+      tokens: burst,
+      refilledAt: Date.now(),
+      // see pauseHost
+      pausedUntil: 0,
+      queue: Promise.resolve(),
+    });
+  }
+  const bucket = hostBuckets.get(host);
+  const refill = () => {
+    const now = Date.now();
+    bucket.tokens =
+      delay === 0
+        ? burst
+        : Math.min(burst, bucket.tokens + (now - bucket.refilledAt) / delay);
+    bucket.refilledAt = now;
+  };
+  const turn = bucket.queue.then(async () => {
+    const paused = bucket.pausedUntil - Date.now();
+    if (paused > 0) {
+      await sleep(paused);
+    }
+    refill();
+    if (bucket.tokens < 1) {
+      await sleep((1 - bucket.tokens) * delay);
+      refill();
+    }
+    bucket.tokens -= 1;
+  });
+  bucket.queue = turn;
+  return turn;
+}
+
+// Holds back all further requests to the host for the given time.
+function pauseHost(host, ms) {
+  const bucket = hostBuckets.get(host);
+  if (bucket !== undefined) {
+    bucket.pausedUntil = Math.max(bucket.pausedUntil, Date.now() + ms);
+  }
+}
+
+// The delay in milliseconds that the Retry-After header of the response
+// asks for, in seconds or as a date, undefined without one.
+function retryAfter(response) {
+  const value = response && response.headers && response.headers["retry-after"];
+  if (value === undefined) {
+    return undefined;
+  }
+  const seconds = Number(value);
+  const ms = isNaN(seconds) ? Date.parse(value) - Date.now() : seconds * 1000;
+  return isNaN(ms) ? undefined : Math.max(0, ms);
+}
+
+// An error of the updater, by what failed: a request ("fetch"), reading a
+// response ("parse") or a file like the cache ("io"). Errors of requests keep
+// the `response` and `code` of the failed request, and the number of
+// `retries` before giving up.
+export class UpdaterError extends Error {
+  constructor(kind, message, cause) {
+    super(message, { cause });
+    this.name = "UpdaterError";
+    this.kind = kind;
+    if (cause !== undefined) {
+      this.response = cause.response;
+      this.code = cause.code;
+    }
+  }
+}
+
+// Rate limits, server errors, timeouts and dropped connections are worth
+// retrying, other errors (like 404) are not.
+function isTransient(error) {
+  if (error.response) {
+    return error.response.status === 429 || error.response.status >= 500;
+  }
+  return ["ECONNABORTED", "ETIMEDOUT", "ECONNRESET", "EAI_AGAIN"].includes(
+    error.code
+  );
+}
+
+const urlHash = (url) => crypto.createHash("sha1").update(url).digest("hex");
+const cachePath = (url) => settings.cacheDir + "/" + urlHash(url);
+
+// Serves a request from the fixture directory, whose files have the format of
+// the cache files, so a copy of the cache can be used as fixtures. Requests
+// without a fixture fail with a 404 response.
+async function fixtureRequest({ url, validateStatus }) {
+  const path = settings.fixturesDir + "/" + urlHash(url);
+  const found = fs.existsSync(path);
+  const response = {
+    status: found ? 200 : 404,
+    data: found
+      ? JSON.parse(fs.readFileSync(path, { encoding: "utf-8" })).data
+      : undefined,
+    request: { res: { responseUrl: url } },
+  };
+  if (!found && !(validateStatus && validateStatus(404))) {
+    let error = new Error("No fixture for " + url);
+    error.response = response;
+    throw error;
+  }
+  return response;
+}
+
+// All requests go through this transport, so it can be replaced without
+// touching the fetchers.
+export const http = {
+  request: (config) =>
+    settings.fixturesDir === undefined
+      ? axios.request(config)
+      : fixtureRequest(config),
+};
+
+// arXiv and DBLP ask clients to identify themselves with a way to reach
+// their operators.
+const userAgent =
+  config.user_agent ||
+  "alps-updater/1.0 (+" +
+    config.site_url +
+    (credentials.mailto ? "; mailto:" + credentials.mailto : "") +
+    ")";
+
+// Sends the request with the headers common to all requests.
+const send = (request) =>
+  http.request({
+    ...request,
+    headers: { "User-Agent": userAgent, ...request.headers },
+  });
+
+// Fetches the url, or returns the cached response if it is recent enough.
+// Older cached responses are revalidated with their ETag or Last-Modified
+// date. Resolves to an object with the response body in `data`, like axios.
+export async function get(url, options = {}) {
+  const path = cachePath(url);
+  const useCache = settings.fixturesDir === undefined;
+  let cached;
+  if (useCache && !settings.noCache && fs.existsSync(path)) {
+    try {
+      cached = JSON.parse(fs.readFileSync(path, { encoding: "utf-8" }));
+    } catch (error) {
+      throw new UpdaterError("io", "Unreadable cache file " + path, error);
+    }
+    const maxAge = settings.cacheTtl * 24 * 60 * 60 * 1000;
+    if (Date.now() - cached.fetchedAt < maxAge) {
+      return { data: cached.data };
+    }
+  }
+  let conditional = {};
+  if (cached !== undefined && cached.etag !== undefined) {
+    conditional["If-None-Match"] = cached.etag;
+  }
+  if (cached !== undefined && cached.lastModified !== undefined) {
+    conditional["If-Modified-Since"] = cached.lastModified;
+  }
+
+  const host = new URL(url).host;
+  let response;
+  for (let retries = 0; response === undefined; retries++) {
+    await waitForHost(host);
+    try {
+      response = await send({
+        method: "get",
+        url,
+        timeout: 30000,
+        validateStatus: (status) =>
+          (status >= 200 && status < 300) ||
+          (status === 304 && cached !== undefined),
+        ...options,
+        headers: { ...conditional, ...options.headers },
+      });
+    } catch (error) {
+      // the server's wish holds for all requests to it, not only this one
+      const requested = retryAfter(error.response);
+      if (requested !== undefined) {
+        pauseHost(host, requested);
+      }
+      if (retries === settings.maxRetries || !isTransient(error)) {
+        let failure = new UpdaterError(
+          "fetch",
+          "Request failed: " + url,
+          error
+        );
+        failure.retries = retries;
+        throw failure;
+      }
+      // exponential backoff with jitter, starting at 1-2 seconds, unless
+      // the server says how long to wait
+      const delay =
+        requested !== undefined
+          ? requested
+          : 1000 * 2 ** retries * (1 + Math.random());
+      log.warn(
+        "Request to " +
+          host +
+          " failed (" +
+          (error.response ? error.response.status : error.code) +
+          "), retry " +
+          (retries + 1) +
+          "/" +
+          settings.maxRetries +
+          " in " +
+          (delay / 1000).toFixed(1) +
+          "s: " +
+          url
+      );
+      if (requested === undefined) {
+        await sleep(delay);
+      }
+    }
+  }
+  if (response.status === 304) {
+    log.debug("Cached response is still valid: " + url);
+    response = {
+      ...response,
+      data: cached.data,
+      // servers may leave out the validators in the 304 response
+      headers: {
+        etag: cached.etag,
+        "last-modified": cached.lastModified,
+        ...response.headers,
+      },
+    };
+  }
+  if (useCache) {
+    const headers = response.headers || {};
+    try {
+      fs.mkdirSync(settings.cacheDir, { recursive: true });
+      fs.writeFileSync(
+        path,
+        JSON.stringify({
+          url,
+          fetchedAt: Date.now(),
+          etag: headers.etag,
+          lastModified: headers["last-modified"],
+          data: response.data,
+        })
+      );
+    } catch (error) {
+      throw new UpdaterError("io", "Could not write cache file " + path, error);
+    }
+  }
+  return response;
+}
+
+// Line-based unified diff of two texts with `context` unchanged lines around
+// every change.
+export function unifiedDiff(name, before, after, context = 2) {
+  const a = before.split("\n");
+  const b = after.split("\n");
+
+  // lcs[i][j] is the length of the longest common subsequence of a[i..], b[j..]
+  let lcs = Array.from({ length: a.length + 1 }, () =>
+    new Array(b.length + 1).fill(0)
+  );
+  for (let i = a.length - 1; i >= 0; i--) {
+    for (let j = b.length - 1; j >= 0; j--) {
+      lcs[i][j] =
+        a[i] === b[j]
+          ? lcs[i + 1][j + 1] + 1
+          : Math.max(lcs[i + 1][j], lcs[i][j + 1]);
+    }
+  }
+
+  let ops = [];
+  let i = 0;
+  let j = 0;
+  while (i < a.length || j < b.length) {
+    if (i < a.length && j < b.length && a[i] === b[j]) {
+      ops.push({ type: " ", line: a[i], i: i++, j: j++ });
+    } else if (
+      i < a.length &&
+      (j === b.length || lcs[i + 1][j] >= lcs[i][j + 1])
+    ) {
+      ops.push({ type: "-", line: a[i], i: i++, j });
+    } else {
+      ops.push({ type: "+", line: b[j], i, j: j++ });
+    }
+  }
+
+  let lines = ["--- " + name, "+++ " + name];
+  let k = 0;
+  while (k < ops.length) {
+    if (ops[k].type === " ") {
+      k++;
+      continue;
+    }
+    // extend the hunk as long as the next change is within 2 * context lines
+    let start = Math.max(0, k - context);
+    let end = k;
+    let unchanged = 0;
+    while (end < ops.length && unchanged <= 2 * context) {
+      unchanged = ops[end].type === " " ? unchanged + 1 : 0;
+      end++;
+    }
+    end = Math.min(ops.length, end - unchanged + context);
+    const hunk = ops.slice(start, end);
+    const oldLen = hunk.filter((op) => op.type !== "+").length;
+    const newLen = hunk.filter((op) => op.type !== "-").length;
+    lines.push(
+      "@@ -" +
+        (hunk[0].i + 1) +
+        "," +
+        oldLen +
+        " +" +
+        (hunk[0].j + 1) +
+        "," +
+        newLen +
+        " @@"
+    );
+    hunk.forEach((op) => lines.push(op.type + op.line));
+    k = end;
+  }
+  return lines.join("\n");
+}
+
+export const isPreprint = (pub) =>
+  pub.kind === "preprint" || pub.name === "arXiv" || pub.name === "CoRR";
+
+// The values of the kind field of publications.
+const publicationKinds = ["preprint", "conference", "workshop", "journal"];
+
+// Workshops publish proceedings like conferences, only their name tells them
+// apart.
+const proceedingsKind = (venue) =>
+  /workshop/i.test(venue || "") ? "workshop" : "conference";
+
+// The kind of the publication as far as its venue and DBLP key tell,
+// undefined if they do not.
+function publicationKind(pub) {
+  if (isPreprint(pub)) {
+    return "preprint";
+  }
+  const key = typeof pub.dblp_key === "string" ? pub.dblp_key : "";
+  if (key.startsWith("journals/")) {
+    return "journal";
+  }
+  if (key.startsWith("conf/")) {
+    return proceedingsKind(pub.name);
+  }
+  return undefined;
+}
+
+// Combining characters for the LaTeX accent commands.
+const latexAccents = {
+  "`": "\u0300",
+  "'": "\u0301",
+  "^": "\u0302",
+  "~": "\u0303",
+  "=": "\u0304",
+  u: "\u0306",
+  ".": "\u0307",
+  '"': "\u0308",
+  H: "\u030b",
+  v: "\u030c",
+  c: "\u0327",
+  k: "\u0328",
+};
+const latexLetters = {
+  ss: "ß",
+  aa: "å",
+  AA: "Å",
+  ae: "æ",
+  AE: "Æ",
+  oe: "œ",
+  OE: "Œ",
+  o: "ø",
+  O: "Ø",
+  l: "ł",
+  L: "Ł",
+};
+// Latin letters that do not decompose into a letter and an accent.
+const foldedLetters = {
+  ß: "ss",
+  æ: "ae",
+  Æ: "AE",
+  œ: "oe",
+  Œ: "OE",
+  ø: "o",
+  Ø: "O",
+  ł: "l",
+  Ł: "L",
+  đ: "d",
+  Đ: "D",
+  ı: "i",
+};
+// Readable replacements for common commands in inline math.
+const latexMath = {
+  alpha: "α",
+  beta: "β",
+  gamma: "γ",
+  delta: "δ",
+  epsilon: "ε",
+  varepsilon: "ε",
+  eta: "η",
+  lambda: "λ",
+  mu: "μ",
+  pi: "π",
+  sigma: "σ",
+  tau: "τ",
+  phi: "φ",
+  Delta: "Δ",
+  Omega: "Ω",
+  Theta: "Θ",
+  ell: "ℓ",
+  infty: "∞",
+  le: "≤",
+  leq: "≤",
+  ge: "≥",
+  geq: "≥",
+  neq: "≠",
+  approx: "≈",
+  cdot: "·",
+  times: "×",
+  sqrt: "√",
+  sum: "∑",
+  in: "∈",
+  log: "log",
+  ln: "ln",
+  min: "min",
+  max: "max",
+  poly: "poly",
+  polylog: "polylog",
+};
+
+// Turns LaTeX in a text from arXiv into plain text: accents become unicode
+// characters, grouping braces and repeated whitespace are dropped. Inline
+// math ($...$) is kept as it is, unless `convertMath` is set, which replaces
+// common commands by unicode and drops the dollars.
+function cleanLatex(text, convertMath = config.latex_math === "unicode") {
+  const plain = (part) =>
+    part
+      .replace(
+        /\\([`'^~=."])\s*(?:\{\s*(\\?[a-zA-Z])\s*\}|(\\?[a-zA-Z]))/g,
+        (_, accent, braced, bare) =>
+          (braced || bare).replace(/^\\/, "") + latexAccents[accent]
+      )
+      .replace(
+        /\\([uHvck])(?:\s*\{\s*(\\?[a-zA-Z])\s*\}|\s+([a-zA-Z]))/g,
+        (_, accent, braced, bare) =>
+          (braced || bare).replace(/^\\/, "") + latexAccents[accent]
+      )
+      .replace(
+        /\\(ss|aa|AA|ae|AE|oe|OE|o|O|l|L)(?![a-zA-Z])\s*/g,
+        (_, letter) => latexLetters[letter]
+      )
+      .replace(/\\&/g, "&")
+      .replace(/[{}]/g, "");
+  const math = (part) =>
+    part
+      .slice(1, -1)
+      .replace(/\\([a-zA-Z]+)\s*/g, (command, name) =>
+        name in latexMath ? latexMath[name] + " " : command
+      )
+      .replace(/[{}]/g, "")
+      .replace(/\s+([)\],])/g, "$1")
+      .trim();
+  return text
+    .split(/(\$[^$]+\$)/)
+    .map((part, i) =>
+      i % 2 === 0 ? plain(part) : convertMath ? math(part) : part
+    )
+    .join("")
+    .normalize("NFC")
+    .replace(/\s+/g, " ")
+    .trim();
+}
+
+// The text without accents, like "Lukasz Muller" for "Łukasz Müller".
+const foldAccents = (text) =>
+  text
+    .normalize("NFKD")
+    .replace(/(\p{Script=Latin})\p{M}+/gu, "$1")
+    .replace(/[ßæÆœŒøØłŁđĐı]/g, (letter) => foldedLetters[letter])
+    .normalize("NFC");
+
+// A URL-safe identifier of the paper from the first words of its title and
+// the year of its first publication, like
+// "online-bipartite-matching-with-predicted-degrees-2021".
+export function paperSlug(paper) {
+  const words = foldAccents(cleanLatex(paper.title, true))
+    .toLowerCase()
+    .split(/[^a-z0-9]+/)
+    .filter((word) => word !== "");
+  let base = "";
+  for (const word of words) {
+    if (base !== "" && base.length + word.length >= 60) {
+      break;
+    }
+    base = base === "" ? word : base + "-" + word;
+  }
+  const year = Math.min(...paper.publications.map((pub) => pub.year));
+  return (base || "paper") + "-" + year;
+}
+
+// The slugs of the papers by id (the name of their file). Papers with the
+// same slug get a short hash of their id appended, so that slugs are unique
+// and stay the same as long as the file keeps its name.
+export function paperSlugs(papers) {
+  const slugs = papers.map(({ id, paper }) => [id, paperSlug(paper)]);
+  let counts = new Map();
+  slugs.forEach(([, slug]) => counts.set(slug, (counts.get(slug) || 0) + 1));
+  return new Map(
+    slugs.map(([id, slug]) => [
+      id,
+      counts.get(slug) === 1
+        ? slug
+        : slug +
+          "-" +
+          crypto.createHash("sha1").update(id).digest("hex").slice(0, 6),
+    ])
+  );
+}
+
+// Lower case without punctuation and accents, so that titles and names
+// compare equal however their letters are encoded or transliterated.
+function normalizeTitle(title) {
+  return foldAccents(title.normalize("NFKC"))
+    .toLowerCase()
+    .replace(/[^\p{L}\p{M}\p{N}\s]/gu, " ")
+    .replace(/\s+/g, " ")
+    .trim();
+}
+
+// The share of the elements of two sets that both have.
+function jaccard(a, b) {
+  const shared = [...a].filter((element) => b.has(element)).length;
+  const all = a.size + b.size - shared;
+  return all === 0 ? 1 : shared / all;
+}
+
+const trigrams = (text) => {
+  const padded = "  " + text + " ";
+  return new Set(
+    [...Array(Math.max(0, padded.length - 2)).keys()].map((i) =>
+      padded.slice(i, i + 3)
+    )
+  );
+};
+
+// The ways to compare two normalized titles, selected by the similarity
+// option.
+const similarityMethods = {
+  // edit distance relative to the length of the longer title
+  levenshtein: (a, b) => {
+    const length = Math.max(a.length, b.length);
+    return length === 0 ? 1 : 1 - fastls.get(a, b) / length;
+  },
+  // shared words, regardless of their order
+  jaccard: (a, b) => jaccard(new Set(a.split(" ")), new Set(b.split(" "))),
+  // shared sequences of three characters, which tolerates typos and
+  // reordered words
+  trigram: (a, b) => jaccard(trigrams(a), trigrams(b)),
+  // the mean of the edit distance and the shared words
+  blend: (a, b) =>
+    (similarityMethods.levenshtein(a, b) + similarityMethods.jaccard(a, b)) /
+    2,
+};
+
+if (!(config.similarity in similarityMethods)) {
+  throw new Error(
+    "Unknown similarity method " +
+      config.similarity +
+      " in updater.yml, expected one of " +
+      Object.keys(similarityMethods).join(", ")
+  );
+}
+
+// Suffixes that mark a version of a paper rather than a different paper.
+const versionSuffix =
+  /[\s:.,-]*[([]?\b(extended abstract|(full|extended) version)[)\]]?\.?\s*$/i;
+
+// The normalized title without version suffixes, and the part before the
+// colon of a subtitle, unless it has fewer than three words (a prefix like
+// "Caching" is the title of many papers).
+function titleVariants(title) {
+  const text = cleanLatex(title, true).replace(versionSuffix, "");
+  const colon = text.indexOf(":");
+  const prefix = colon === -1 ? "" : normalizeTitle(text.slice(0, colon));
+  return [
+    normalizeTitle(text),
+    prefix.split(" ").length >= 3 ? prefix : undefined,
+  ];
+}
+
+// Similarity of two titles between 0 (nothing in common) and 1 (equal up to
+// case, punctuation, accents and whitespace), by the configured method. A
+// title with a subtitle also counts as similar as its part before the colon,
+// so that "Caching with Predictions: Robustness and Consistency" matches
+// "Caching with Predictions".
+export function similarityScore(a, b) {
+  const score = similarityMethods[config.similarity];
+  const [fullA, prefixA] = titleVariants(a);
+  const [fullB, prefixB] = titleVariants(b);
+  // Most search results have nothing to do with the paper, counting shared
+  // words rules them out faster than comparing the titles.
+  const wordsA = new Set(fullA.split(" "));
+  const wordsB = new Set(fullB.split(" "));
+  const shared = [...wordsA].filter((word) => wordsB.has(word)).length;
+  if (shared < Math.min(config.min_shared_words, wordsA.size, wordsB.size)) {
+    return 0;
+  }
+  return Math.max(
+    score(fullA, fullB),
+    prefixA === undefined ? 0 : score(prefixA, fullB),
+    prefixB === undefined ? 0 : score(fullA, prefixB)
+  );
+}
+
+// The title similarity at which a source added a publication, stored as its
+// match_confidence, so that publications added on a weak match can be told
+// from the others; publications entered by hand have none.
+const matchConfidence = (score) => Math.round(score * 1000) / 1000;
+
+// Returns the search results whose title is at least `threshold` similar to
+// the paper's, best match first. `describe` maps a result to its title, venue
+// and year. Among equally similar results, peer-reviewed and more recent ones
+// win.
+export function rankMatches(paper, hits, describe, threshold) {
+  return hits
+    .map((hit) => ({ hit, ...describe(hit) }))
+    .filter((match) => typeof match.title === "string")
+    .map((match) => ({
+      ...match,
+      score: similarityScore(match.title, paper.title),
+    }))
+    .filter((match) => match.score >= threshold)
+    .sort(
+      (a, b) =>
+        b.score - a.score ||
+        isPreprint({ name: a.venue }) - isPreprint({ name: b.venue }) ||
+        (Number(b.year) || 0) - (Number(a.year) || 0)
+    );
+}
+
+let decisions = fs.existsSync(decisionsFile)
+  ? JSON.parse(fs.readFileSync(decisionsFile, { encoding: "utf-8" }))
+  : {};
+// Created on the first question and closed by closePrompt.
+let prompt;
+// Prompts are shown one after another, even if papers are processed
+// concurrently.
+let promptQueue = Promise.resolve();
+
+export function ask(question) {
+  if (prompt === undefined) {
+    prompt = readline.createInterface({
+      input: process.stdin,
+      output: process.stdout,
+    });
+  }
+  const turn = promptQueue.then(
+    () => new Promise((resolve) => prompt.question(question, resolve))
+  );
+  promptQueue = turn;
+  return turn;
+}
+
+// Closes the terminal input that the questions opened, if any, so that the
+// process can exit.
+export function closePrompt() {
+  if (prompt !== undefined) {
+    prompt.close();
+    prompt = undefined;
+  }
+}
+
+// Whether the maintainer accepts the match as the paper. Answers are stored
+// in decisions.json, so every candidate is only asked about once.
+async function confirmMatch(source, paper, match) {
+  const details = [match.venue, match.year].filter((x) => x !== undefined);
+  const candidate =
+    source +
+    ": " +
+    match.title +
+    (details.length > 0 ? " (" + details.join(", ") + ")" : "");
+  const known = (decisions[paper.title] || {})[candidate];
+  if (known !== undefined) {
+    return known;
+  }
+  const answer = await ask(
+    paper.title +
+      "\n  " +
+      candidate +
+      ", similarity " +
+      match.score.toFixed(3) +
+      "\nAccept this match? [y/N] "
+  );
+  const accepted = answer.trim().toLowerCase().startsWith("y");
+  decisions[paper.title] = { ...decisions[paper.title], [candidate]: accepted };
+  fs.writeFileSync(decisionsFile, JSON.stringify(decisions, null, 2) + "\n");
+  return accepted;
+}
+
+// The best title similarity of the results of each source for each paper,
+// also below the threshold, for updatePaper.
+const bestScores = new WeakMap();
+
+function recordScore(paper, source, score) {
+  const scores = bestScores.get(paper) || {};
+  scores[source] = Math.max(scores[source] || 0, score);
+  bestScores.set(paper, scores);
+}
+
+// rankMatches for the updates: in interactive mode, matches in the uncertain
+// band are kept only if the maintainer confirms them, regardless of the
+// threshold.
+async function findTitleMatches(source, paper, hits, describe, threshold) {
+  const [best] = rankMatches(paper, hits, describe, 0);
+  if (best !== undefined) {
+    recordScore(paper, source, best.score);
+  }
+  if (!settings.interactive) {
+    return rankMatches(paper, hits, describe, threshold);
+  }
+  const { min, max } = config.uncertain;
+  let matches = [];
+  for (const match of rankMatches(
+    paper,
+    hits,
+    describe,
+    Math.min(threshold, min)
+  )) {
+    const uncertain = match.score >= min && match.score < max;
+    if (
+      uncertain
+        ? await confirmMatch(source, paper, match)
+        : match.score >= threshold
+    ) {
+      matches.push(match);
+    }
+  }
+  return matches;
+}
+
+// The writing systems told apart in titles.
+const titleScripts = [
+  "Latin",
+  "Greek",
+  "Cyrillic",
+  "Arabic",
+  "Hebrew",
+  "Han",
+  "Hiragana",
+  "Katakana",
+  "Hangul",
+];
+
+// The writing system most letters of the title are in, if any.
+function titleScript(title) {
+  const counts = titleScripts.map((script) => {
+    const letters = new RegExp("\\p{Script=" + script + "}", "gu");
+    return (title.match(letters) || []).length;
+  });
+  const most = Math.max(...counts);
+  return most > 0 ? titleScripts[counts.indexOf(most)] : undefined;
+}
+
+// Whether the match has mostly the same authors (by last name, as in
+// crossCheck) as the paper and a year within one of a publication's.
+function sameAuthorsAndYear(paper, match) {
+  if (typeof paper.authors !== "string" || match.authors === undefined) {
+    return false;
+  }
+  const lastNames = (names) => new Set(names.map(lastNameKey));
+  const [a, b] = [
+    lastNames(paper.authors.split(",")),
+    lastNames(match.authors),
+  ];
+  const common = [...a].filter((name) => b.has(name)).length;
+  return (
+    common / new Set([...a, ...b]).size >= 0.5 &&
+    paper.publications.some(
+      (pub) => Math.abs(Number(pub.year) - Number(match.year)) <= 1
+    )
+  );
+}
+
+// The results that matched a paper by their authors and year, for the
+// report.
+const authorMatches = new WeakMap();
+
+// findTitleMatches, except that if no title matches, results whose title is
+// in another script than the paper's (like a translation, which no title
+// similarity recognizes) match if their authors and year agree with the
+// paper; `describe` has to give the authors of a result for this.
+async function findMatches(source, paper, hits, describe, threshold) {
+  const matches = await findTitleMatches(
+    source,
+    paper,
+    hits,
+    describe,
+    threshold
+  );
+  if (matches.length > 0) {
+    return matches;
+  }
+  const script = titleScript(paper.title);
+  const translated = rankMatches(paper, hits, describe, 0).filter(
+    (match) =>
+      script !== undefined &&
+      titleScript(match.title) !== undefined &&
+      titleScript(match.title) !== script &&
+      sameAuthorsAndYear(paper, match)
+  );
+  translated.forEach((match) =>
+    authorMatches.set(paper, [
+      ...(authorMatches.get(paper) || []),
+      "matched on " +
+        source +
+        " by authors and year, the title is in another script: " +
+        match.title,
+    ])
+  );
+  return translated;
+}
+
+// Lower case words that start a last name, like in "Jan van der Berg".
+const nameParticles =
+  "da das de del della den der di dos du la le st. ten ter van von zu".split(
+    " "
+  );
+const nameSuffixes = ["Jr.", "Jr", "Sr.", "II", "III", "IV"];
+
+// Splits a full name into the given names and the last name, keeping
+// particles and suffixes with the last name.
+function splitName(name) {
+  let words = name.trim().split(/\s+/);
+  let suffix = [];
+  if (words.length > 2 && nameSuffixes.includes(words.at(-1))) {
+    suffix = [words.pop()];
+    words[words.length - 1] = words.at(-1).replace(/,$/, "");
+  }
+  let start = words.length - 1;
+  while (start > 1 && nameParticles.includes(words[start - 1])) {
+    start--;
+  }
+  return {
+    given: words.slice(0, start),
+    last: [...words.slice(start), ...suffix].join(" "),
+  };
+}
+
+// The last name of the author in a normalized form, so that spellings that
+// differ in case, punctuation or accents compare equal.
+export function lastNameKey(name) {
+  const key = normalizeTitle(splitName(name).last);
+  return key !== "" ? key : normalizeTitle(name);
+}
+
+// The name of an author in the configured author_format.
+function formatAuthor(name) {
+  const { given, last } = splitName(name);
+  if (config.author_format === "full") {
+    return [...given, last].join(" ");
+  }
+  if (config.author_format === "initial_last") {
+    // "Jean-Pierre" becomes "J.-P."
+    const initials = given.map((word) =>
+      word
+        .split("-")
+        .map((part) => part.charAt(0) + ".")
+        .join("-")
+    );
+    return [...initials, last].join(" ");
+  }
+  return last;
+}
+
+const logMatch = (source, paper, match) =>
+  log.info(
+    "Matched " +
+      paper.title +
+      " on " +
+      source +
+      " with similarity " +
+      match.score.toFixed(3)
+  );
+
+function normalizeVenue(name) {
+  return name.toLowerCase().replace(/[^a-z0-9]/g, "");
+}
+
+function venueKey(name) {
+  return normalizeVenue(
+    name.replace(/^proceedings of the /i, "").replace(/\d+/g, "")
+  );
+}
+
+// Returns the canonical name of the venue according to venue_aliases.yml,
+// also trying an abbreviation in trailing parentheses as in "... (SODA)".
+export function canonicalizeVenue(name) {
+  const abbreviation = name.match(/\(([^)]+)\)\s*$/);
+  for (const candidate of [name, abbreviation && abbreviation[1]]) {
+    if (candidate && venueAliases.has(venueKey(candidate))) {
+      return venueAliases.get(venueKey(candidate));
+    }
+  }
+  return name;
+}
+
+// The peer-reviewed venue and year an arXiv comment like "To appear in SODA
+// 2024" or "Accepted at the 35th ACM-SIAM Symposium on Discrete Algorithms
+// (SODA 2024)" names, if venue_aliases.yml knows the venue and the comment
+// gives a year. Only acceptances count: a comment like "Published in SODA
+// 2024" is left to DBLP, and workshops and negated phrases ("not accepted
+// at") name no publication.
+export function announcedVenue(comment) {
+  const phrases = [
+    "to appear (?:in|at)",
+    "accepted (?:at|to|in|for publication in)",
+  ];
+  const announcement = comment.match(
+    new RegExp("\\b(?:" + phrases.join("|") + ")\\s+(?:the\\s+)?([^.;]+)", "i")
+  );
+  if (
+    announcement === null ||
+    /\b(?:not|never)\s+(?:been\s+|be\s+)?$/i.test(
+      comment.slice(0, announcement.index)
+    ) ||
+    /workshop/i.test(announcement[1])
+  ) {
+    return undefined;
+  }
+  const text = announcement[1];
+  const year = text.match(/\b(?:19|20)\d{2}\b/);
+  const abbreviation = text.match(/\(([^)]+)\)/);
+  const venue = [
+    abbreviation && abbreviation[1],
+    text.split(/[,(]/)[0],
+    text.split(/\s/)[0],
+  ]
+    .filter((candidate) => candidate)
+    .map((candidate) =>
+      candidate.replace(/\b(?:19|20)\d{2}\b|\b\d+(?:st|nd|rd|th)\b/gi, "")
+    )
+    .find(
+      (candidate) =>
+        candidate.trim() !== "" && venueAliases.has(venueKey(candidate))
+    );
+  if (venue === undefined || year === null) {
+    return undefined;
+  }
+  return { name: venueAliases.get(venueKey(venue)), year: Number(year[0]) };
+}
+
+// The venue of a DBLP search result: the name of its series according to
+// dblp_venues.yml, else the canonical name of its venue text.
+function dblpVenue(hit) {
+  const series = String(hit.info.key).split("/").slice(0, 2).join("/");
+  if (dblpVenues.has(series)) {
+    return dblpVenues.get(series);
+  }
+  return canonicalizeVenue(hit.info.venue);
+}
+
+// Two publications describe the same work if they share a DOI, or have the
+// same venue and year. Preprints are never merged into peer-reviewed venues.
+function samePublication(a, b) {
+  if (isPreprint(a) !== isPreprint(b)) {
+    return false;
+  }
+  if (a.doi && b.doi) {
+    return a.doi.toLowerCase() === b.doi.toLowerCase();
+  }
+  // the conference and journal versions of a paper are different
+  // publications, even in a venue of the same name
+  if (a.dblp_key && b.dblp_key && a.dblp_key !== b.dblp_key) {
+    return false;
+  }
+  const [kindA, kindB] = [a, b].map((pub) => pub.kind || publicationKind(pub));
+  if (kindA && kindB && (kindA === "journal") !== (kindB === "journal")) {
+    return false;
+  }
+  return (
+    normalizeVenue(canonicalizeVenue(a.name)) ===
+      normalizeVenue(canonicalizeVenue(b.name)) && a.year === b.year
+  );
+}
+
+// Whether a record of the given kind and DOI may describe the publication
+// although it is listed under another venue name: a journal version is not
+// taken for a conference version of the same year, and vice versa.
+function mayDescribe(pub, kind, doi) {
+  const known = pub.kind || publicationKind(pub);
+  return (
+    !isPreprint(pub) &&
+    (kind === undefined ||
+      known === undefined ||
+      (known === "journal") === (kind === "journal")) &&
+    (doi === undefined ||
+      pub.doi === undefined ||
+      pub.doi.toLowerCase() === doi.toLowerCase())
+  );
+}
+
+// Merges duplicate publications of the paper. The entry with the most fields
+// survives and takes over the fields only present in its duplicates. Returns
+// the number of merged publications.
+export function dedupePublications(paper) {
+  let merged = [];
+  paper.publications.forEach((pub) => {
+    const i = merged.findIndex((other) => samePublication(other, pub));
+    if (i === -1) {
+      merged.push(pub);
+      return;
+    }
+    const [rich, poor] =
+      Object.keys(pub).length > Object.keys(merged[i]).length
+        ? [pub, merged[i]]
+        : [merged[i], pub];
+    log.info(
+      "Merged publication at " +
+        poor.name +
+        " into " +
+        rich.name +
+        " for " +
+        paper.title
+    );
+    merged[i] = { ...poor, ...rich };
+  });
+  const count = paper.publications.length - merged.length;
+  paper.publications = merged;
+  return count;
+}
+
+// Puts the preprints first or last (config.preprints) and the other
+// publications in descending order of their date.
+export function sortPublications(paper) {
+  const preprintRank = (pub) =>
+    isPreprint(pub) === (config.preprints === "first") ? 0 : 1;
+  paper.publications.sort(
+    (a, b) =>
+      preprintRank(a) - preprintRank(b) ||
+      (b.year || 0) - (a.year || 0) ||
+      (b.month || 0) - (a.month || 0) ||
+      (b.day || 0) - (a.day || 0)
+  );
+}
+
+const isInteger = (value) => Number.isInteger(value);
+
+// Identifiers that a match_hint may pin, without the version of the preprint.
+const arxivId = /^([a-z-]+\/\d{7}|\d{4}\.\d{4,5})$/;
+const dblpKey = /^[a-z]+(\/[\w-]+)+$/i;
+// An arXiv category like cs.DS or quant-ph.
+const arxivCategory = /^[a-z-]+(\.[a-z-]+)?$/i;
+
+// Returns a list of the rules the paper violates.
+export function validatePaper(paper) {
+  let violations = [];
+  if (paper === null || typeof paper !== "object") {
+    return ["the file does not contain a YAML mapping"];
+  }
+  if (typeof paper.title !== "string" || paper.title.trim() === "") {
+    violations.push("title must be a non-empty string");
+  }
+  if (!Array.isArray(paper.labels)) {
+    violations.push("labels must be a list");
+  }
+  if (!Array.isArray(paper.publications) || paper.publications.length === 0) {
+    violations.push("publications must be a non-empty list");
+    return violations;
+  }
+
+  const maxYear = new Date().getFullYear() + 1;
+  if ("withdrawn" in paper && typeof paper.withdrawn !== "boolean") {
+    violations.push("withdrawn must be true or false");
+  }
+  if (
+    "skip_sources" in paper &&
+    (!Array.isArray(paper.skip_sources) ||
+      paper.skip_sources.some((key) => !(key in config.sources)))
+  ) {
+    violations.push(
+      "skip_sources must be a list of " + Object.keys(config.sources).join(", ")
+    );
+  }
+  if ("match_hint" in paper) {
+    const hint = paper.match_hint;
+    if (hint === null || typeof hint !== "object" || Array.isArray(hint)) {
+      violations.push("match_hint must be a mapping");
+    } else {
+      Object.keys(hint)
+        .filter(
+          (key) => !["arxiv_id", "arxiv_category", "dblp_key"].includes(key)
+        )
+        .forEach((key) => violations.push("match_hint: unknown key " + key));
+      if ("arxiv_id" in hint && !arxivId.test(String(hint.arxiv_id))) {
+        violations.push("match_hint: arxiv_id must be like 2110.11439");
+      }
+      if (
+        "arxiv_category" in hint &&
+        !arxivCategory.test(String(hint.arxiv_category))
+      ) {
+        violations.push("match_hint: arxiv_category must be like cs.DS");
+      }
+      if ("dblp_key" in hint && !dblpKey.test(String(hint.dblp_key))) {
+        violations.push("match_hint: dblp_key must be like conf/soda/Lin22");
+      }
+    }
+  }
+
+  paper.publications.forEach((pub, i) => {
+    const where = "publication " + (i + 1) + ": ";
+    if (typeof pub.name !== "string" || pub.name.trim() === "") {
+      violations.push(where + "name must be a non-empty string");
+    }
+    if ("kind" in pub && !publicationKinds.includes(pub.kind)) {
+      violations.push(where + "kind must be " + publicationKinds.join(", "));
+    }
+    if (!isInteger(pub.year) || pub.year < 1990 || pub.year > maxYear) {
+      violations.push(where + "year must be a number in 1990-" + maxYear);
+      return;
+    }
+    if (
+      "month" in pub &&
+      (!isInteger(pub.month) || pub.month < 0 || pub.month > 11)
+    ) {
+      violations.push(where + "month must be a number in 0-11");
+      return;
+    }
+    if ("day" in pub) {
+      if (!("month" in pub)) {
+        violations.push(where + "day requires a month");
+      } else {
+        const days = new Date(pub.year, pub.month + 1, 0).getDate();
+        if (!isInteger(pub.day) || pub.day < 1 || pub.day > days) {
+          violations.push(where + "day must be a number in 1-" + days);
+        }
+      }
+    }
+    if ("source" in pub && typeof pub.source !== "string") {
+      violations.push(where + "source must be the name of a source");
+    }
+    if (
+      "alt_urls" in pub &&
+      (!Array.isArray(pub.alt_urls) ||
+        pub.alt_urls.some((url) => typeof url !== "string"))
+    ) {
+      violations.push(where + "alt_urls must be a list of urls");
+    }
+    if ("approximate_date" in pub) {
+      if (typeof pub.approximate_date !== "boolean") {
+        violations.push(where + "approximate_date must be true or false");
+      } else if (!("month" in pub)) {
+        violations.push(where + "approximate_date requires a month");
+      }
+    }
+    if (
+      "match_confidence" in pub &&
+      !(
+        typeof pub.match_confidence === "number" &&
+        pub.match_confidence >= 0 &&
+        pub.match_confidence <= 1
+      )
+    ) {
+      violations.push(where + "match_confidence must be a number in 0-1");
+    }
+    if ("to_appear" in pub && typeof pub.to_appear !== "boolean") {
+      violations.push(where + "to_appear must be true or false");
+    }
+  });
+  return violations;
+}
+
+// Reads the value of a BibTeX field starting at position i, delimited by
+// braces, by quotes or not at all. Returns the raw value and the position
+// after it.
+function bibtexValue(text, i) {
+  if (text[i] !== "{" && text[i] !== '"') {
+    const value = text.slice(i).match(/^[^,}\s]*/)[0];
+    return [value, i + value.length];
+  }
+  let depth = 0;
+  for (let j = i; j < text.length; j++) {
+    if (text[j] === "{") {
+      depth++;
+    } else if (text[j] === "}") {
+      depth--;
+    }
+    const closed =
+      text[i] === "{" ? depth === 0 : depth === 0 && j > i && text[j] === '"';
+    if (closed) {
+      return [text.slice(i + 1, j), j + 1];
+    }
+  }
+  return [text.slice(i + 1), text.length];
+}
+
+// Splits a BibTeX entry into its type, key and fields. Field names are lower
+// case, values lose LaTeX grouping braces and repeated whitespace, and the
+// authors are split into a list. Returns undefined for unparsable entries.
+export function parseBibtex(text) {
+  const head = text.match(/@(\w+)\s*\{\s*([^,\s]+)\s*,/);
+  if (head === null) {
+    return undefined;
+  }
+  let fields = {};
+  let raw = {};
+  const fieldName = /\s*([\w-]+)\s*=\s*/y;
+  fieldName.lastIndex = head.index + head[0].length;
+  let match;
+  while ((match = fieldName.exec(text)) !== null) {
+    const start = fieldName.lastIndex;
+    const [value, end] = bibtexValue(text, start);
+    const name = match[1].toLowerCase();
+    fields[name] = value.replace(/[{}]/g, "").replace(/\s+/g, " ").trim();
+    raw[name] = text.slice(start, end);
+    fieldName.lastIndex = end + text.slice(end).match(/^\s*,?/)[0].length;
+  }
+  return {
+    type: head[1].toLowerCase(),
+    key: head[2],
+    fields,
+    // the values as written, with their delimiters
+    raw,
+    authors: fields.author ? fields.author.split(" and ") : [],
+  };
+}
+
+// Fields of the DBLP bibtex that change without the publication changing.
+const volatileBibtexFields = ["timestamp", "biburl", "bibsource"];
+
+// The bibtex entry without the volatile fields, in the layout of DBLP and
+// with the given key, if any. Unparsable entries are returned as they are.
+export function cleanBibtex(text, key) {
+  const entry = parseBibtex(text);
+  if (entry === undefined) {
+    return text.trim();
+  }
+  const lines = Object.entries(entry.raw)
+    .filter(([name]) => !volatileBibtexFields.includes(name))
+    .map(
+      ([name, value]) =>
+        "  " +
+        name.padEnd(9) +
+        " = " +
+        // continuation lines are aligned with the start of the value
+        value.replace(/\s*\n\s*/g, "\n" + " ".repeat(15))
+    );
+  const head = "@" + entry.type + "{" + (key || entry.key) + ",\n";
+  return head + lines.join(",\n") + "\n}";
+}
+
+// The bibtex entry to cite the paper by: the stored bibtex of a peer-reviewed
+// publication or else of a preprint, cleaned. Without stored bibtex, a
+// minimal entry with the given key is made up from the paper file.
+export function paperBibtex(paper, key) {
+  const stored = [
+    ...paper.publications.filter((pub) => !isPreprint(pub)),
+    ...paper.publications.filter(isPreprint),
+  ].find(
+    (pub) =>
+      typeof pub.bibtex === "string" && parseBibtex(pub.bibtex) !== undefined
+  );
+  if (stored !== undefined) {
+    return cleanBibtex(stored.bibtex);
+  }
+  const pub =
+    paper.publications.find((pub) => !isPreprint(pub)) ||
+    paper.publications[0];
+  const fields = {
+    author:
+      typeof paper.authors === "string"
+        ? paper.authors.split(/\s*,\s*/).join(" and ")
+        : undefined,
+    title: paper.title,
+    howpublished: pub.name,
+    year: pub.year,
+    url: pub.url,
+  };
+  const lines = Object.entries(fields)
+    .filter(([, value]) => value !== undefined)
+    .map(([name, value]) => "  " + name.padEnd(9) + " = {" + value + "}");
+  return "@misc{" + key + ",\n" + lines.join(",\n") + "\n}";
+}
+
+// The 0-based month of a BibTeX month field like "jan", "January" or "1",
+// undefined if there is none.
+function bibtexMonth(value) {
+  if (value === undefined) {
+    return undefined;
+  }
+  const number = Number(value);
+  if (Number.isInteger(number) && number >= 1 && number <= 12) {
+    return number - 1;
+  }
+  const i = "jan feb mar apr may jun jul aug sep oct nov dec"
+    .split(" ")
+    .indexOf(value.trim().slice(0, 3).toLowerCase());
+  return i === -1 ? undefined : i;
+}
+
+// Problems with the bibtex stored on the publications of the paper, which
+// hint that it belongs to a different DBLP record than the publication.
+export function checkBibtex(paper, threshold) {
+  let problems = [];
+  paper.publications
+    .filter((pub) => "bibtex" in pub)
+    .forEach((pub) => {
+      const entry = parseBibtex(pub.bibtex);
+      if (entry === undefined) {
+        problems.push("bibtex of the " + pub.name + " version is unparsable");
+        return;
+      }
+      const { title, year } = entry.fields;
+      if (title && similarityScore(title, paper.title) < threshold) {
+        problems.push(
+          "bibtex title of the " + pub.name + " version is " + title
+        );
+      }
+      if (year && Number(year) !== pub.year) {
+        problems.push(
+          "bibtex year of the " +
+            pub.name +
+            " version is " +
+            year +
+            " instead of " +
+            pub.year
+        );
+      }
+    });
+  return problems;
+}
+
+// Problems that do not make the paper invalid, but should be checked by a
+// maintainer: malformed arXiv urls, and (if DBLP was searched) papers that
+// still only have preprints, which may have been published meanwhile.
+const arxivAbsUrl = /^https:\/\/arxiv\.org\/abs\/\d{4}\.\d{4,5}$/;
+
+function needsAttention(paper, searchedDblp) {
+  let reasons = [];
+  paper.publications
+    .filter((pub) => pub.name === "arXiv" && typeof pub.url === "string")
+    .filter((pub) => !arxivAbsUrl.test(pub.url))
+    .forEach((pub) => reasons.push("unexpected arXiv url " + pub.url));
+  if (paper.withdrawn === true) {
+    reasons.push("withdrawn or retracted");
+  }
+  if (searchedDblp && paper.publications.every(isPreprint)) {
+    reasons.push("only preprints, no peer-reviewed publication found");
+  }
+  return reasons;
+}
+
+// Order of the fields in written paper files, other fields follow at the end.
+const paperFieldOrder = [
+  "title",
+  "authors",
+  "authors_detailed",
+  "labels",
+  "publications",
+  "abstract",
+  "citation_count",
+  "openalex_id",
+  "withdrawn",
+  "match_hint",
+  "skip_sources",
+];
+const publicationFieldOrder = [
+  "name",
+  // the name the website shows, if it differs
+  "displayName",
+  "kind",
+  "year",
+  "month",
+  "day",
+  "approximate_date",
+  "to_appear",
+  "updated_year",
+  "updated_month",
+  "updated_day",
+  "url",
+  "alt_urls",
+  "doi",
+  "dblp_key",
+  "openreview_id",
+  "citation_count",
+  "source",
+  "match_confidence",
+  "bibtex",
+];
+
+// The fields that every paper or publication needs stay, --reset-field may
+// remove the others the updater knows.
+const requiredFields = ["title", "labels", "publications", "name", "year"];
+export const resettableFields = [
+  ...paperFieldOrder,
+  ...publicationFieldOrder,
+].filter((field) => !requiredFields.includes(field));
+
+// Removes the field from the paper and all of its publications, wherever it
+// is one of their fields ("bibtex" of publications, "citation_count" of
+// both), so that the next update fills it in anew. Returns the number of
+// removed values.
+export function resetField(paper, field) {
+  const holders = [
+    ...(paperFieldOrder.includes(field) ? [paper] : []),
+    ...(publicationFieldOrder.includes(field) ? paper.publications : []),
+  ];
+  const removed = holders.filter((holder) => field in holder);
+  removed.forEach((holder) => delete holder[field]);
+  return removed.length;
+}
+
+function orderFields(object, order) {
+  let ordered = {};
+  order
+    .filter((key) => key in object)
+    .forEach((key) => (ordered[key] = object[key]));
+  Object.keys(object)
+    .filter((key) => !order.includes(key))
+    .forEach((key) => (ordered[key] = object[key]));
+  return ordered;
+}
+
+// The paper with its fields (and those of its publications) in the
+// canonical order.
+function canonicalPaper(paper) {
+  let ordered = orderFields(paper, paperFieldOrder);
+  if (Array.isArray(ordered.publications)) {
+    ordered.publications = ordered.publications.map((pub) =>
+      orderFields(pub, publicationFieldOrder)
+    );
+  }
+  return ordered;
+}
+
+// The content of the paper file in the canonical format.
+export const formatPaper = (paper) =>
+  yaml.dump(canonicalPaper(paper), { lineWidth: -1 });
+
+// A hash of the paper in the canonical format, which tells whether an update
+// changed the paper, whatever the order of its fields and the formatting of
+// its file.
+export const paperHash = (paper) =>
+  crypto.createHash("sha1").update(formatPaper(paper)).digest("hex");
+
+// Whether the paper has everything the sources could add: authors and a
+// peer-reviewed publication with a url and bibtex.
+export const hasCompleteMetadata = (paper) =>
+  "authors" in paper &&
+  paper.publications.some(
+    (pub) => !isPreprint(pub) && "url" in pub && "bibtex" in pub
+  );
+
+// Labels of the vocabulary that the abstract or the arXiv categories suggest
+// for the paper, leaving out those the paper already has.
+export function suggestLabels(paper, abstract, categories) {
+  const text = abstract.toLowerCase();
+  return labelVocabulary
+    .filter(
+      (rule) =>
+        !paper.labels.includes(rule.label) &&
+        (rule.keywords.some((keyword) =>
+          text.includes(keyword.toLowerCase())
+        ) ||
+          rule.categories.some((category) => categories.includes(category)))
+    )
+    .map((rule) => rule.label);
+}
+
+// The relevance of a text to the collection by the rubric of relevance.yml,
+// with the phrases it contains (ignoring case) and whether it reaches the
+// threshold.
+export function relevance(text) {
+  const lower = text.replace(/\s+/g, " ").toLowerCase();
+  const phrases = Object.keys(relevanceRubric.phrases).filter((phrase) =>
+    lower.includes(phrase.toLowerCase())
+  );
+  const score = phrases.reduce(
+    (sum, phrase) => sum + relevanceRubric.phrases[phrase],
+    0
+  );
+  return { score, phrases, relevant: score >= relevanceRubric.threshold };
+}
+
+// The fields of the paper and its publications the updater does not know,
+// like "notes" or "publication 2: pages". They are kept as they are, after
+// the known fields.
+export function unknownFields(paper) {
+  return [
+    ...Object.keys(paper).filter((field) => !paperFieldOrder.includes(field)),
+    ...paper.publications.flatMap((pub, i) =>
+      Object.keys(pub)
+        .filter((field) => !publicationFieldOrder.includes(field))
+        .map((field) => "publication " + (i + 1) + ": " + field)
+    ),
+  ];
+}
+
+// The labels of the paper that are not in the vocabulary, each with the
+// closest label of the vocabulary by edit distance (ignoring case).
+export function unknownLabels(paper) {
+  return paper.labels
+    .filter((label) => !labelVocabulary.some((rule) => rule.label === label))
+    .map((label) => {
+      const [closest] = labelVocabulary
+        .map((rule) => ({
+          suggestion: rule.label,
+          distance: fastls.get(
+            String(label).toLowerCase(),
+            rule.label.toLowerCase()
+          ),
+        }))
+        .sort((a, b) => a.distance - b.distance);
+      return { label, ...closest };
+    });
+}
+
+// Requests the url and follows its redirects one by one. Returns the status
+// of the last response, the url after all redirects and the chain of
+// redirects, each with its url and status.
+export async function checkLink(url, maxHops = 10) {
+  const request = async (method, target) => {
+    await waitForHost(new URL(target).host);
+    return send({
+      method,
+      url: target,
+      timeout: 30000,
+      maxRedirects: 0,
+      validateStatus: () => true,
+    });
+  };
+  let chain = [];
+  let current = url;
+  try {
+    for (;;) {
+      let response = await request("head", current);
+      // some servers do not implement HEAD requests
+      if (response.status === 405 || response.status === 501) {
+        response = await request("get", current);
+      }
+      const location = (response.headers || {}).location;
+      const redirect = response.status >= 300 && response.status < 400;
+      if (!redirect || location === undefined || chain.length === maxHops) {
+        return { status: response.status, finalUrl: current, chain };
+      }
+      chain.push({ url: current, status: response.status });
+      current = new URL(location, current).href;
+    }
+  } catch (error) {
+    return { error: error.code || error.message, finalUrl: current, chain };
+  }
+}
+
+// Like Promise.all(items.map(fn)), but with at most `limit` pending calls.
+export async function mapConcurrent(items, limit, fn) {
+  let results = new Array(items.length);
+  let next = 0;
+  const worker = async () => {
+    while (next < items.length) {
+      const i = next++;
+      results[i] = await fn(items[i]);
+    }
+  };
+  await Promise.all(Array.from({ length: limit }, worker));
+  return results;
+}
+
+// The named entities that occur in titles, author and venue names.
+const htmlEntities = {
+  amp: "&",
+  lt: "<",
+  gt: ">",
+  quot: '"',
+  apos: "'",
+  nbsp: " ",
+};
+
+// Decodes the HTML entities in the text, also those encoded twice like
+// "&amp;amp;", of which the XML parser only decodes the outer one.
+export function decodeEntities(text) {
+  let decoded = text;
+  let previous;
+  do {
+    previous = decoded;
+    decoded = decoded.replace(
+      /&(?:#x([0-9a-f]+)|#(\d+)|([a-z]+));/gi,
+      (entity, hex, decimal, name) => {
+        if (hex !== undefined || decimal !== undefined) {
+          return String.fromCodePoint(
+            hex !== undefined ? parseInt(hex, 16) : Number(decimal)
+          );
+        }
+        return htmlEntities[name.toLowerCase()] || entity;
+      }
+    );
+  } while (decoded !== previous);
+  return decoded;
+}
+
+function decodeStrings(node) {
+  if (typeof node === "string") {
+    return decodeEntities(node);
+  }
+  if (Array.isArray(node)) {
+    return node.map(decodeStrings);
+  }
+  if (node !== null && typeof node === "object") {
+    return Object.fromEntries(
+      Object.entries(node).map(([key, value]) => [key, decodeStrings(value)])
+    );
+  }
+  return node;
+}
+
+// Parses an XML response of arXiv or DBLP with its attributes (like the
+// categories of arXiv entries and the ORCIDs of DBLP authors), decoding the
+// entities left in its text, so that titles and names are stored as they
+// read.
+function parseXml(data) {
+  return decodeStrings(new XMLParser({ ignoreAttributes: false }).parse(data));
+}
+
+// The entries of a response of the arXiv API. Throws if the response is not
+// an Atom feed or if arXiv reports an error (like a malformed query), so that
+// these cases are not mistaken for a search without results.
+export function parseArxivFeed(data) {
+  let feed;
+  try {
+    feed = parseXml(data).feed;
+  } catch (error) {
+    feed = undefined;
+  }
+  if (feed === undefined || feed === null || typeof feed !== "object") {
+    throw new UpdaterError("parse", "unparsable arXiv response");
+  }
+  const entries = [feed.entry]
+    .flat()
+    .filter((entry) => entry !== undefined && entry !== null);
+  const error = entries.find((entry) => String(entry.id).includes("/errors"));
+  if (error !== undefined) {
+    throw new UpdaterError(
+      "fetch",
+      "arXiv error: " + String(error.summary).trim()
+    );
+  }
+  if (Number(xmlText(feed["opensearch:totalResults"])) === 0) {
+    return [];
+  }
+  return entries.filter((entry) => typeof entry.title === "string");
+}
+
+// Returns the entries of an arXiv title search for the paper. If the authors
+// of the paper are known, the search is restricted to entries by one of them,
+// unless that finds nothing.
+export async function searchArxiv(paper) {
+  const query = paper.title.replace("-", " ").split(" ").join("+");
+  const search = async (searchQuery) => {
+    let info = await get(
+      "http://export.arxiv.org/api/query?max_results=" +
+        config.max_results +
+        "&search_query=" +
+        searchQuery
+    );
+    return parseArxivFeed(info.data);
+  };
+  const authors = typeof paper.authors === "string" ? paper.authors : "";
+  // arXiv matches ascii surnames, like au:Mitzenmacher
+  const surnames = authors
+    .split(",")
+    .map((name) => name.trim())
+    .filter((name) => name !== "")
+    .map((name) =>
+      foldAccents(
+        splitName(name)
+          .last.split(" ")
+          .filter((word) => !nameSuffixes.includes(word))
+          .at(-1) || ""
+      ).replace(/[^A-Za-z-]/g, "")
+    )
+    .filter((surname) => surname !== "");
+  if (surnames.length > 0) {
+    const hits = await search(
+      "%28" +
+        query +
+        "%29+AND+%28" +
+        surnames.map((surname) => "au:" + surname).join("+OR+") +
+        "%29"
+    );
+    if (hits.length > 0) {
+      return hits;
+    }
+    log.debug("No arXiv results by the authors of " + paper.title);
+  }
+  return search(query);
+}
+
+// The authors and year of the arXiv and DBLP matches of each paper, for
+// crossCheck.
+const matchedRecords = new WeakMap();
+
+function recordMatch(paper, source, authors, year, publications = []) {
+  matchedRecords.set(paper, {
+    ...matchedRecords.get(paper),
+    [source]: { authors, year: Number(year), publications },
+  });
+}
+
+// Reasons to doubt that the arXiv and DBLP matches of the paper are the same
+// work: mostly different authors, or a DBLP publication more than a year
+// before or after the preprint.
+export function crossCheck(paper) {
+  const { arxiv, dblp } = matchedRecords.get(paper) || {};
+  if (arxiv === undefined || dblp === undefined) {
+    return [];
+  }
+  let reasons = [];
+  const lastNames = (authors) => new Set(authors.map(lastNameKey));
+  const [a, b] = [lastNames(arxiv.authors), lastNames(dblp.authors)];
+  const common = [...a].filter((name) => b.has(name)).length;
+  if (common / new Set([...a, ...b]).size < 0.5) {
+    reasons.push(
+      "arXiv authors " +
+        arxiv.authors.join(", ") +
+        " differ from DBLP authors " +
+        dblp.authors.join(", ")
+    );
+  }
+  // A peer-reviewed version rarely precedes the preprint by more than the
+  // configured slack of its kind.
+  const inversions = dblp.publications.filter(
+    (pub) => arxiv.year - pub.year > config.year_slack[pub.kind]
+  );
+  inversions.forEach((pub) =>
+    reasons.push(
+      "DBLP " +
+        pub.kind +
+        " publication at " +
+        pub.venue +
+        " (" +
+        pub.year +
+        ") predates the arXiv preprint (" +
+        arxiv.year +
+        ")"
+    )
+  );
+  if (inversions.length === 0 && Math.abs(arxiv.year - dblp.year) > 1) {
+    reasons.push(
+      "arXiv year " + arxiv.year + " is far from DBLP year " + dblp.year
+    );
+  }
+  return reasons;
+}
+
+// The arXiv id in an abs or pdf url like https://arxiv.org/pdf/2011.09076.pdf,
+// without the version.
+function arxivIdOf(url) {
+  const match = url.match(
+    /arxiv\.org\/(?:abs|pdf)\/([a-z-]+\/\d{7}|\d{4}\.\d{4,5})(?:v\d+)?(?:\.pdf)?$/
+  );
+  return match === null ? undefined : match[1];
+}
+
+// The entry with the given arXiv id, undefined if arXiv does not know it.
+async function fetchArxivEntry(id) {
+  let info = await get("http://export.arxiv.org/api/query?id_list=" + id);
+  return parseArxivFeed(info.data).find((hit) =>
+    String(hit.id).includes("arxiv.org/abs/")
+  );
+}
+
+// Whether the arXiv entry was withdrawn: arXiv prefixes the title of the
+// withdrawing version, and its comment says so.
+const arxivWithdrawn = (hit) =>
+  /^\s*withdrawn\b/i.test(hit.title) ||
+  /\bwithdrawn\b/i.test(String(xmlText(hit["arxiv:comment"]) || ""));
+
+// Flags the paper as withdrawn or retracted. The entry is kept, the website
+// marks it.
+function markWithdrawn(paper, reason) {
+  if (paper.withdrawn !== true) {
+    paper.withdrawn = true;
+    log.warn(paper.title + " was " + reason);
+  }
+}
+
+// The primary category of the arXiv entry, like cs.DS.
+const primaryCategory = (hit) =>
+  (hit["arxiv:primary_category"] || {})["@_term"];
+
+async function updateFromArxiv(paper, settings) {
+  const describe = (hit) => ({
+    title: hit.title,
+    venue: "arXiv",
+    year: new Date(hit.published).getFullYear(),
+    authors: [hit.author].flat().map((a) => a.name),
+  });
+  // Entries in other categories are not the paper, however similar their
+  // title.
+  const category = (paper.match_hint || {}).arxiv_category;
+  const inCategory = (hit) =>
+    category === undefined ||
+    String(primaryCategory(hit)).toLowerCase() ===
+      String(category).toLowerCase();
+  // The match hint or an arXiv url in the paper file identifies the preprint
+  // exactly, the title search is only needed without them.
+  const hinted = (paper.match_hint || {}).arxiv_id;
+  const id =
+    hinted !== undefined
+      ? hinted
+      : paper.publications
+          .filter((pub) => pub.name === "arXiv" && typeof pub.url === "string")
+          .map((pub) => arxivIdOf(pub.url))
+          .find((found) => found !== undefined);
+  let match;
+  if (id !== undefined) {
+    const hit = await fetchArxivEntry(id);
+    if (hit !== undefined && hinted === undefined && !inCategory(hit)) {
+      log.warn(
+        "arXiv entry " +
+          id +
+          " of " +
+          paper.title +
+          " is in " +
+          primaryCategory(hit) +
+          ", not " +
+          category
+      );
+    } else if (hit !== undefined) {
+      log.debug("Fetched arXiv entry " + id + " of " + paper.title);
+      match = {
+        hit,
+        ...describe(hit),
+        score: similarityScore(hit.title, paper.title),
+      };
+    }
+  }
+  if (match !== undefined) {
+    recordScore(paper, "arXiv", match.score);
+  }
+  if (match === undefined && hinted !== undefined) {
+    log.warn("arXiv has no entry " + hinted + " hinted for " + paper.title);
+    return false;
+  }
+  if (match === undefined) {
+    const hits = await searchArxiv(paper);
+    [match] = await findMatches(
+      "arXiv",
+      paper,
+      hits.filter(inCategory),
+      describe,
+      settings.threshold
+    );
+    if (match === undefined) {
+      log.debug(
+        (hits.length === 0
+          ? "No arXiv results for "
+          : "None of " + hits.length + " arXiv results matches ") + paper.title
+      );
+      return false;
+    }
+  }
+  logMatch("arXiv", paper, match);
+  const hit = match.hit;
+  if (arxivWithdrawn(hit)) {
+    markWithdrawn(paper, "withdrawn on arXiv");
+  }
+  recordMatch(
+    paper,
+    "arxiv",
+    [hit.author].flat().map((a) => a.name),
+    match.year
+  );
+
+  if (!("authors" in paper)) {
+    paper.authors = [hit.author]
+      .flat()
+      .map((a) => formatAuthor(cleanLatex(a.name)))
+      .join(", ");
+    log.info("Setting authors of " + paper.title + " to " + paper.authors);
+  }
+
+  if (!("abstract" in paper) && typeof hit.summary === "string") {
+    // arXiv hard-wraps the abstracts
+    paper.abstract = cleanLatex(hit.summary);
+    log.info("Added abstract to " + paper.title);
+  }
+
+  let date = new Date(hit.published);
+  let year = date.getFullYear();
+  let month = date.getMonth();
+  let day = date.getDate();
+  // The entry is published with the first version, later versions move the
+  // updated date. The year stays that of the first version, so that the
+  // papers sort stably, the latest revision is recorded separately.
+  let revised = {};
+  let latest = new Date(hit.updated);
+  if (!isNaN(latest) && latest.toDateString() !== date.toDateString()) {
+    revised = {
+      updated_year: latest.getFullYear(),
+      updated_month: latest.getMonth(),
+      updated_day: latest.getDate(),
+    };
+  }
+  // Accepted papers often say so in the comment long before DBLP lists
+  // them; the publication is marked until DBLP does.
+  const comment = xmlText(hit["arxiv:comment"]);
+  const announced =
+    typeof comment === "string" ? announcedVenue(comment) : undefined;
+  if (
+    announced !== undefined &&
+    !paper.publications.some((pub) => pub.name === announced.name)
+  ) {
+    log.info(
+      "Added publication at " +
+        announced.name +
+        " announced on arXiv to " +
+        paper.title
+    );
+    paper.publications.push({
+      ...announced,
+      to_appear: true,
+      match_confidence: matchConfidence(match.score),
+    });
+  }
+  let pdfurl =
+    hit.id
+      //.replace("abs", "pdf")
+      .replace(/v\d+/, "")
+      .replace("http", "https");// + ".pdf";
+
+  if (!paper.publications.some((pub) => pub.name === "arXiv")) {
+    log.info("Added arXiv preprint to " + paper.title);
+    paper.publications.push({
+      name: "arXiv",
+      year,
+      month,
+      day,
+      ...revised,
+      url: pdfurl,
+      match_confidence: matchConfidence(match.score),
+    });
+  } else {
+    // updated in place, so that updatePaper still knows the publication
+    Object.assign(
+      paper.publications.find((pub) => pub.name === "arXiv"),
+      { url: pdfurl, year, month, day, ...revised }
+    );
+  }
+  return true;
+}
+
+// Text content of an element parsed with attributes.
+const xmlText = (node) =>
+  node !== null && typeof node === "object" ? node["#text"] : node;
+
+// Hosts whose links lead to the full text without a subscription.
+const openAccessHosts = [
+  "proceedings.mlr.press",
+  "proceedings.neurips.cc",
+  "papers.nips.cc",
+  "openreview.net",
+  "drops.dagstuhl.de",
+  "ojs.aaai.org",
+  "www.ijcai.org",
+];
+
+// Orders the electronic editions of a DBLP record, best link first: DOIs,
+// which outlive publisher pages, then open access links, then the others,
+// and preprints last.
+function rankEditions(ee) {
+  const rank = (node) => {
+    const url = String(xmlText(node));
+    const host = url.replace(/^https?:\/\//, "").split("/")[0];
+    if (host === "doi.org" || host === "dx.doi.org") {
+      return 0;
+    }
+    if (host.endsWith("arxiv.org")) {
+      return 3;
+    }
+    const open =
+      (node !== null && typeof node === "object" && node["@_type"] === "oa") ||
+      openAccessHosts.includes(host);
+    return open ? 1 : 2;
+  };
+  const urls = [ee]
+    .flat()
+    .filter((node) => node !== undefined && node !== null)
+    .map((node, i) => ({ url: String(xmlText(node)), rank: rank(node), i }))
+    .sort((a, b) => a.rank - b.rank || a.i - b.i)
+    .map(({ url }) => url);
+  return [...new Set(urls)];
+}
+
+// The DBLP record with the given key in the form of a search result,
+// undefined if DBLP does not know the key.
+async function fetchDblpRecord(key) {
+  let info;
+  try {
+    info = await get("https://dblp.org/rec/" + key + ".xml");
+  } catch (error) {
+    if (error.response && error.response.status === 404) {
+      return undefined;
+    }
+    throw error;
+  }
+  // the only element of the document is named after the record type
+  const [type, record] =
+    Object.entries(parseXml(info.data).dblp || {}).find(
+      ([name]) => !name.startsWith("@_")
+    ) || [];
+  if (record === undefined) {
+    return undefined;
+  }
+  return {
+    info: {
+      key,
+      title: xmlText(record.title),
+      venue: record.journal || record.booktitle,
+      year: record.year,
+      type: type === "article" ? "Journal Articles" : "Conference Papers",
+      ee: record.ee,
+      authors: { author: record.author },
+    },
+  };
+}
+
+async function updateFromDBLP(paper, settings) {
+  const describe = (hit) => ({
+    title: hit.info.title,
+    venue: dblpVenue(hit),
+    year: hit.info.year,
+    authors: [(hit.info.authors || {}).author]
+      .flat()
+      .filter((a) => a !== undefined)
+      .map((a) => xmlText(a).replace(/ \d{4}$/, "")),
+  });
+  // The match hint pins the record, no search is needed.
+  const hinted = (paper.match_hint || {}).dblp_key;
+  let matches;
+  if (hinted !== undefined) {
+    const hit = await fetchDblpRecord(hinted);
+    if (hit === undefined) {
+      log.warn("DBLP has no record " + hinted + " hinted for " + paper.title);
+      return false;
+    }
+    log.debug("Fetched DBLP record " + hinted + " of " + paper.title);
+    matches = [
+      {
+        hit,
+        ...describe(hit),
+        score: similarityScore(hit.info.title, paper.title),
+      },
+    ];
+    recordScore(paper, "DBLP", matches[0].score);
+  } else {
+    let info = await get(
+      "https://dblp.org/search/publ/api?h=" +
+        config.max_results +
+        "&q=" +
+        paper.title.replace("-", " ").split(" ").join("+")
+    );
+
+    let dataObj;
+    try {
+      dataObj = parseXml(info.data);
+    } catch (error) {
+      throw new UpdaterError("parse", "unparsable DBLP response", error);
+    }
+    if (!dataObj.result || !dataObj.result.hits) {
+      throw new UpdaterError("parse", "unparsable DBLP response");
+    }
+    let hits = [dataObj.result.hits.hit].flat();
+
+    matches = await findMatches(
+      "DBLP",
+      paper,
+      hits.filter((hit) => hit !== undefined && hit.info.venue !== "CoRR"),
+      describe,
+      settings.threshold
+    );
+  }
+  if (matches.length === 0) {
+    return false;
+  }
+  logMatch("DBLP", paper, matches[0]);
+
+  const authors = [matches[0].hit.info.authors.author].flat().map((a) => ({
+    // DBLP disambiguates homonymous authors by a suffix like " 0001"
+    name: xmlText(a).replace(/ \d{4}$/, ""),
+    orcid: a["@_orcid"],
+  }));
+  // journal versions often appear years after the preprint, compare with the
+  // earliest publication
+  recordMatch(
+    paper,
+    "dblp",
+    authors.map((a) => a.name),
+    Math.min(...matches.map((match) => Number(match.year))),
+    matches.map(({ hit }) => ({
+      venue: dblpVenue(hit),
+      year: Number(hit.info.year),
+      kind: /journal/i.test(hit.info.type) ? "journal" : "conference",
+    }))
+  );
+  if (!("authors" in paper)) {
+    paper.authors = authors.map((a) => formatAuthor(a.name)).join(", ");
+    log.info("Setting authors of " + paper.title + " to " + paper.authors);
+  }
+  if (
+    !("authors_detailed" in paper) &&
+    authors.some((a) => a.orcid !== undefined)
+  ) {
+    paper.authors_detailed = authors.map((a) =>
+      a.orcid === undefined ? { name: a.name } : a
+    );
+    log.info("Added detailed authors to " + paper.title);
+  }
+
+  // A paper may have been published at several venues (e.g. at a conference
+  // and in a journal), take the best match for each of them.
+  for (const { hit, score } of matches) {
+    const venue = dblpVenue(hit);
+    // a publication announced on arXiv gives way to the record
+    const announced = paper.publications.find(
+      (pub) => pub.name === venue && pub.to_appear
+    );
+    if (announced !== undefined) {
+      log.info("DBLP lists the " + venue + " version of " + paper.title);
+      paper.publications.splice(paper.publications.indexOf(announced), 1);
+    }
+    let pub = paper.publications.find((pub) => pub.name === venue);
+    if (pub === undefined) {
+      log.info("Added publication at " + venue + " to " + paper.title);
+      const [url, ...alternatives] = rankEditions(hit.info.ee);
+      pub = {
+        name: venue,
+        year: hit.info.year,
+        match_confidence: matchConfidence(score),
+      };
+      if (url !== undefined) {
+        pub.url = url;
+      }
+      if (alternatives.length > 0) {
+        pub.alt_urls = alternatives;
+      }
+      paper.publications.push(pub);
+    }
+    if (!("kind" in pub) && pub.year === hit.info.year) {
+      const kind = publicationKind({ name: venue, dblp_key: hit.info.key });
+      if (kind !== undefined) {
+        pub.kind = kind;
+      }
+    }
+    if (!("bibtex" in pub) && pub.year === hit.info.year) {
+      pub.dblp_key = hit.info.key;
+      if (settings.deferBibtex) {
+        pendingBibtex.push({ paper, pub });
+      } else {
+        await addDblpBibtex(paper, pub);
+      }
+    }
+    if (pub.month === undefined && pub.year === hit.info.year) {
+      guessMonth(paper, pub);
+    }
+  }
+  return true;
+}
+
+// Adds the bibtex of the DBLP record of the publication (its dblp_key), and
+// the month of the bibtex if the publication has none. Returns whether that
+// worked.
+async function addDblpBibtex(paper, pub) {
+  try {
+    const bib = await get("https://dblp.org/rec/" + pub.dblp_key + ".bib");
+    pub.bibtex = cleanBibtex(bib.data);
+  } catch (error) {
+    log.warn("Could not fetch the bibtex of " + pub.dblp_key);
+    return false;
+  }
+  log.info("Added bibtex of the " + pub.name + " version of " + paper.title);
+  const entry = parseBibtex(pub.bibtex);
+  const month = entry && bibtexMonth(entry.fields.month);
+  // the month of the record replaces a guessed one (see guessMonth)
+  if (
+    (pub.month === undefined || pub.approximate_date) &&
+    month !== undefined
+  ) {
+    pub.month = month;
+    delete pub.approximate_date;
+    log.info("Set month of the " + pub.name + " version of " + paper.title);
+  }
+  return true;
+}
+
+// The publications whose bibtex updates left to fetchPendingBibtex, with
+// their papers.
+let pendingBibtex = [];
+
+// Fetches the bibtex of the DBLP records that updates matched while
+// settings.deferBibtex was set, `concurrency` at a time. Returns the
+// publications that got their bibtex, with their papers.
+export async function fetchPendingBibtex(concurrency) {
+  const pending = pendingBibtex;
+  pendingBibtex = [];
+  if (pending.length > 0) {
+    log.info("Fetching the bibtex of " + pending.length + " DBLP records");
+  }
+  const added = await mapConcurrent(pending, concurrency, ({ paper, pub }) =>
+    addDblpBibtex(paper, pub)
+  );
+  return pending.filter((_, i) => added[i]);
+}
+
+// Whether the bibtex of a DBLP match of the paper is left to
+// fetchPendingBibtex.
+export const hasPendingBibtex = (paper) =>
+  pendingBibtex.some((pending) => pending.paper === paper);
+
+// The peer-reviewed publications of the paper without bibtex.
+export const missingBibtex = (paper) =>
+  paper.publications.filter((pub) => !isPreprint(pub) && !("bibtex" in pub));
+
+// Fills in the missing bibtex of the paper: publications with a DBLP key get
+// the bibtex of their record directly, and only if others remain, DBLP is
+// searched for the paper.
+async function updateBibtexFromDBLP(paper, settings) {
+  let found = false;
+  for (const pub of missingBibtex(paper)) {
+    if (typeof pub.dblp_key === "string") {
+      found = (await addDblpBibtex(paper, pub)) || found;
+    }
+  }
+  if (missingBibtex(paper).some((pub) => !("dblp_key" in pub))) {
+    found = (await updateFromDBLP(paper, settings)) || found;
+  }
+  return found;
+}
+
+// Replaces the sources for --only-missing-bibtex.
+export const bibtexSource = {
+  key: "dblp",
+  name: "DBLP",
+  update: updateBibtexFromDBLP,
+};
+
+// Sets the month of a conference publication to the month the venue usually
+// takes place (config.venue_months), marked as approximate. Only papers with
+// a dated preprint get one, so that the guess orders papers of a year rather
+// than dating an otherwise undated paper.
+function guessMonth(paper, pub) {
+  const configured = Object.keys(config.venue_months || {}).find(
+    (venue) => canonicalizeVenue(venue) === pub.name
+  );
+  const month =
+    configured === undefined
+      ? undefined
+      : bibtexMonth(String(config.venue_months[configured]));
+  const dated = paper.publications.some(
+    (other) => isPreprint(other) && other.month !== undefined
+  );
+  if (
+    month === undefined ||
+    !dated ||
+    (pub.kind || publicationKind(pub)) === "journal"
+  ) {
+    return;
+  }
+  pub.month = month;
+  pub.approximate_date = true;
+  log.info("Guessed month of the " + pub.name + " version of " + paper.title);
+}
+
+// Differences between the stored data of the paper and the records it is
+// pinned to: the arXiv entry of its match hint or arXiv url, and the DBLP
+// records of its match hint and publications. Each is reported with the
+// stored and the fetched value; nothing is changed.
+export async function verifyPaper(paper) {
+  let discrepancies = [];
+  const report = (source, field, stored, fetched) =>
+    discrepancies.push({ source, field, stored, fetched });
+  const sameAuthors = (names) => {
+    const keys = (list) => [...new Set(list.map(lastNameKey))].sort().join();
+    return keys(paper.authors.split(",")) === keys(names);
+  };
+
+  const hint = paper.match_hint || {};
+  const arxivPub = paper.publications.find(
+    (pub) => pub.name === "arXiv" && typeof pub.url === "string"
+  );
+  const arxivId =
+    hint.arxiv_id !== undefined
+      ? hint.arxiv_id
+      : arxivPub && arxivIdOf(arxivPub.url);
+  if (arxivId !== undefined) {
+    const source = "arXiv " + arxivId;
+    const hit = await fetchArxivEntry(arxivId);
+    if (hit === undefined) {
+      report(source, "entry", arxivId, "none");
+    } else {
+      const title = cleanLatex(hit.title).replace(/\s+/g, " ").trim();
+      const threshold = config.sources.arxiv.threshold;
+      if (similarityScore(title, paper.title) < threshold) {
+        report(source, "title", paper.title, title);
+      }
+      const authors = [hit.author].flat().map((a) => cleanLatex(a.name));
+      if (typeof paper.authors === "string" && !sameAuthors(authors)) {
+        report(source, "authors", paper.authors, authors.join(", "));
+      }
+      const published = new Date(hit.published);
+      const pub = paper.publications.find((pub) => pub.name === "arXiv");
+      const date = {
+        year: published.getFullYear(),
+        month: published.getMonth(),
+        day: published.getDate(),
+      };
+      Object.entries(date)
+        .filter(([field]) => pub !== undefined && field in pub)
+        .filter(([field, value]) => pub[field] !== value)
+        .forEach(([field, value]) =>
+          report(source, "arXiv " + field, pub[field], value)
+        );
+    }
+  }
+
+  const keys = [
+    ...(hint.dblp_key !== undefined ? [hint.dblp_key] : []),
+    ...paper.publications
+      .map((pub) => pub.dblp_key)
+      .filter((key) => key !== undefined),
+  ];
+  for (const key of new Set(keys)) {
+    const source = "DBLP " + key;
+    const hit = await fetchDblpRecord(key);
+    if (hit === undefined) {
+      report(source, "record", key, "none");
+      continue;
+    }
+    const authors = [hit.info.authors.author]
+      .flat()
+      .filter((a) => a !== undefined)
+      .map((a) => xmlText(a).replace(/ \d{4}$/, ""));
+    if (typeof paper.authors === "string" && !sameAuthors(authors)) {
+      report(source, "authors", paper.authors, authors.join(", "));
+    }
+    const pub = paper.publications.find((pub) => pub.dblp_key === key);
+    if (pub === undefined) {
+      continue;
+    }
+    const venue = dblpVenue(hit);
+    if (pub.name !== venue) {
+      report(source, "venue", pub.name, venue);
+    }
+    if (pub.year !== Number(hit.info.year)) {
+      report(source, pub.name + " year", pub.year, Number(hit.info.year));
+    }
+  }
+  return discrepancies;
+}
+
+async function updateFromSemanticScholar(paper, settings) {
+  let info = await get(
+    "https://api.semanticscholar.org/graph/v1/paper/search?query=" +
+      encodeURIComponent(paper.title) +
+      "&limit=" +
+      config.max_results +
+      "&fields=title,venue,year,externalIds,citationCount,publicationTypes",
+    { headers: { "x-api-key": credentials.semanticScholarKey } }
+  );
+  let hits = info.data.data || [];
+  const [match] = await findMatches(
+    "Semantic Scholar",
+    paper,
+    hits,
+    (hit) => ({ title: hit.title, venue: hit.venue, year: hit.year }),
+    settings.threshold
+  );
+  if (match === undefined) {
+    return false;
+  }
+  logMatch("Semantic Scholar", paper, match);
+  const hit = match.hit;
+
+  const venue = hit.venue ? canonicalizeVenue(hit.venue) : undefined;
+  const types = hit.publicationTypes || [];
+  const kind = types.includes("JournalArticle")
+    ? "journal"
+    : types.includes("Conference")
+    ? proceedingsKind(venue)
+    : undefined;
+  let doi = hit.externalIds && hit.externalIds.DOI;
+  // records with the DOI arXiv registers describe the preprint
+  const arxivDoi = /^10\.48550\/arxiv\./i.test(doi || "");
+  // like CrossRef records, a record of another venue name only describes a
+  // publication of the same kind and DOI; without one, the counts go to the
+  // preprint
+  const published = arxivDoi
+    ? undefined
+    : paper.publications.find(
+        (pub) => venue !== undefined && pub.name === venue
+      ) ||
+      paper.publications.find(
+        (pub) => pub.year === hit.year && mayDescribe(pub, kind, doi)
+      );
+  let pub =
+    published || paper.publications.find((pub) => pub.name === "arXiv");
+  if (pub === undefined) {
+    return true;
+  }
+
+  if (hit.citationCount !== null && hit.citationCount !== undefined) {
+    pub.citation_count = hit.citationCount;
+  }
+  // the DOI of the published version does not belong to the preprint
+  if (doi && !("doi" in pub) && isPreprint(pub) === arxivDoi) {
+    log.info("Added DOI " + doi + " to " + paper.title);
+    pub.doi = doi;
+  }
+  return true;
+}
+
+async function updateFromOpenAlex(paper, settings) {
+  let info = await get(
+    "https://api.openalex.org/works?search=" +
+      encodeURIComponent(paper.title) +
+      "&per-page=" +
+      config.max_results +
+      mailtoParameter("mailto")
+  );
+  const [match] = await findMatches(
+    "OpenAlex",
+    paper,
+    info.data.results || [],
+    (work) => ({
+      title: work.display_name,
+      venue:
+        work.primary_location &&
+        work.primary_location.source &&
+        work.primary_location.source.display_name,
+      year: work.publication_year,
+    }),
+    settings.threshold
+  );
+  if (match === undefined) {
+    return false;
+  }
+  logMatch("OpenAlex", paper, match);
+
+  // ids are urls like https://openalex.org/W3128384394
+  const id = match.hit.id.split("/").at(-1);
+  if (paper.openalex_id !== id) {
+    log.info("Set OpenAlex id of " + paper.title + " to " + id);
+    paper.openalex_id = id;
+  }
+  if (typeof match.hit.cited_by_count === "number") {
+    paper.citation_count = match.hit.cited_by_count;
+  }
+  return true;
+}
+
+// The [year, month, day] a CrossRef work was published, as far as known.
+const crossrefDate = (item) =>
+  (item.published || item.issued || { "date-parts": [[]] })["date-parts"][0];
+
+async function updateFromCrossref(paper, settings) {
+  let info = await get(
+    "https://api.crossref.org/works?rows=" +
+      config.max_results +
+      "&query.bibliographic=" +
+      encodeURIComponent(paper.title) +
+      mailtoParameter("mailto")
+  );
+  let items = info.data.message.items.filter(
+    (item) => item.type !== "posted-content" && item.title !== undefined
+  );
+
+  const [match] = await findMatches(
+    "CrossRef",
+    paper,
+    items,
+    (item) => ({
+      title: item.title[0],
+      venue: (item["container-title"] || [])[0],
+      year: crossrefDate(item)[0],
+    }),
+    settings.threshold
+  );
+  if (match === undefined) {
+    return false;
+  }
+  logMatch("CrossRef", paper, match);
+  const best = match.hit;
+  // retraction notices are linked from the retracted work
+  const retracted = (best["updated-by"] || []).some(
+    (update) => update.type === "retraction"
+  );
+  if (retracted) {
+    markWithdrawn(paper, "retracted according to CrossRef");
+  }
+
+  const container = (best["container-title"] || [])[0];
+  const venue = container && canonicalizeVenue(container);
+  const [year, month, day] = crossrefDate(best);
+  // CrossRef months are 1-based, the paper files use 0-based months.
+  const month0 = month === undefined ? undefined : month - 1;
+  const kind =
+    best.type === "journal-article"
+      ? "journal"
+      : best.type === "proceedings-article"
+      ? proceedingsKind(venue)
+      : undefined;
+  let pub =
+    paper.publications.find((pub) => pub.name === venue) ||
+    paper.publications.find(
+      (pub) => pub.year === year && mayDescribe(pub, kind, best.DOI)
+    );
+
+  if (pub === undefined) {
+    if (venue === undefined || year === undefined) {
+      return true;
+    }
+    log.info("Added publication at " + venue + " to " + paper.title);
+    pub = {
+      name: venue,
+      year,
+      url: "https://doi.org/" + best.DOI,
+      match_confidence: matchConfidence(match.score),
+    };
+    paper.publications.push(pub);
+  }
+
+  if (!("doi" in pub)) {
+    log.info("Added DOI " + best.DOI + " to " + paper.title);
+    pub.doi = best.DOI;
+  }
+  if (!("kind" in pub) && kind !== undefined) {
+    pub.kind = kind === "journal" ? kind : proceedingsKind(pub.name);
+  }
+  // a known month replaces a guessed one
+  if ((!("month" in pub) || pub.approximate_date) && month0 !== undefined) {
+    delete pub.approximate_date;
+    pub.month = month0;
+  }
+  if (!("day" in pub) && day !== undefined) {
+    pub.day = day;
+  }
+  return true;
+}
+
+async function updateFromPubMed(paper, settings) {
+  const eutils = "https://eutils.ncbi.nlm.nih.gov/entrez/eutils/";
+  // NCBI asks tools to identify themselves
+  const identification =
+    "&tool=alps-updater" +
+    mailtoParameter("email") +
+    (credentials.ncbiKey
+      ? "&api_key=" + encodeURIComponent(credentials.ncbiKey)
+      : "");
+  let search = await get(
+    eutils +
+      "esearch.fcgi?db=pubmed&retmode=json&retmax=" +
+      config.max_results +
+      "&term=" +
+      encodeURIComponent(paper.title + "[Title]") +
+      identification
+  );
+  const ids = search.data.esearchresult.idlist;
+  if (ids.length === 0) {
+    return false;
+  }
+  let summaries = await get(
+    eutils +
+      "esummary.fcgi?db=pubmed&retmode=json&id=" +
+      ids.join(",") +
+      identification
+  );
+  const result = summaries.data.result;
+  const articles = result.uids.map((uid) => result[uid]);
+
+  const [match] = await findMatches(
+    "PubMed",
+    paper,
+    articles,
+    (article) => ({
+      title: article.title,
+      venue: article.source,
+      year: parseInt(article.pubdate),
+    }),
+    settings.threshold
+  );
+  if (match === undefined) {
+    return false;
+  }
+  logMatch("PubMed", paper, match);
+  const article = match.hit;
+  const venue = canonicalizeVenue(article.source);
+  const year = match.year;
+  const doi = (
+    (article.articleids || []).find((id) => id.idtype === "doi") || {}
+  ).value;
+
+  let pub =
+    paper.publications.find((pub) => pub.name === venue) ||
+    paper.publications.find(
+      (pub) => pub.year === year && mayDescribe(pub, "journal", doi)
+    );
+  if (pub === undefined) {
+    if (!venue || isNaN(year)) {
+      return true;
+    }
+    log.info("Added publication at " + venue + " to " + paper.title);
+    pub = {
+      name: venue,
+      kind: "journal",
+      year,
+      url: "https://pubmed.ncbi.nlm.nih.gov/" + article.uid + "/",
+      match_confidence: matchConfidence(match.score),
+    };
+    paper.publications.push(pub);
+  }
+  if (doi && !("doi" in pub)) {
+    log.info("Added DOI " + doi + " to " + paper.title);
+    pub.doi = doi;
+  }
+  return true;
+}
+
+async function updateFromOpenReview(paper, settings) {
+  let info = await get(
+    "https://api2.openreview.net/notes/search?limit=" +
+      config.max_results +
+      "&query=" +
+      encodeURIComponent(paper.title)
+  );
+
+  const matches = await findMatches(
+    "OpenReview",
+    paper,
+    info.data.notes,
+    (note) => ({ title: note.content.title && note.content.title.value }),
+    settings.threshold
+  );
+  matches.forEach(({ hit: note, score }) => {
+    const venueId = note.content.venueid && note.content.venueid.value;
+    if (!venueId) {
+      return;
+    }
+    // accepted papers have venue ids like "NeurIPS.cc/2022/Conference",
+    // rejected or withdrawn ones end in "Rejected_Submission" and similar
+    const accepted = venueId.match(
+      /^([^/]+?)(?:\.cc|\.org)?\/(\d{4})\/Conference$/
+    );
+    if (accepted === null) {
+      return;
+    }
+    const venue = canonicalizeVenue(accepted[1]);
+    const year = Number(accepted[2]);
+
+    const pub = paper.publications.find((pub) => pub.name === venue);
+    if (pub === undefined) {
+      log.info("Added publication at " + venue + " to " + paper.title);
+      paper.publications.push({
+        name: venue,
+        kind: proceedingsKind(venue),
+        year,
+        url: "https://openreview.net/forum?id=" + note.forum,
+        openreview_id: note.forum,
+        match_confidence: matchConfidence(score),
+      });
+    } else if (pub.year !== year) {
+      log.warn(
+        "Ignoring OpenReview entry of " +
+          paper.title +
+          " at " +
+          venue +
+          " " +
+          year +
+          ", the paper is recorded for " +
+          pub.year
+      );
+    } else if (!("openreview_id" in pub)) {
+      pub.openreview_id = note.forum;
+    }
+  });
+  return matches.length > 0;
+}
+
+// All sources, in the order in which they are queried.
+export const allSources = [
+  { key: "arxiv", name: "arXiv", update: updateFromArxiv },
+  { key: "dblp", name: "DBLP", update: updateFromDBLP },
+  { key: "crossref", name: "CrossRef", update: updateFromCrossref },
+  { key: "openreview", name: "OpenReview", update: updateFromOpenReview },
+  {
+    key: "semantic_scholar",
+    name: "Semantic Scholar",
+    update: updateFromSemanticScholar,
+  },
+  { key: "openalex", name: "OpenAlex", update: updateFromOpenAlex },
+  { key: "pubmed", name: "PubMed", update: updateFromPubMed },
+];
+
+// The sources enabled in updater.yml that can be queried, which excludes
+// those without their required credentials (see missingCredentials).
+export const enabledSources = () =>
+  allSources.filter(
+    (source) =>
+      config.sources[source.key].enabled &&
+      missingCredentials(source.key).length === 0
+  );
+
+// The values of the fields of the paper by name, where the fields of a
+// publication are named after it, like "SODA year" (the names --verify
+// reports). Comparing them before and after a source tells what it set.
+export function fieldValues(paper) {
+  let values = new Map();
+  Object.entries(paper)
+    .filter(([field]) => field !== "publications")
+    .forEach(([field, value]) => values.set(field, JSON.stringify(value)));
+  paper.publications.forEach((pub) =>
+    Object.entries(pub)
+      .filter(([field]) => field !== "source")
+      .forEach(([field, value]) =>
+        values.set(pub.name + " " + field, JSON.stringify(value))
+      )
+  );
+  return values;
+}
+
+// Updates the paper in place from the given sources (except those its
+// skip_sources names), then merges and sorts its publications. Returns what
+// happened: the outcome per source name ("matched", "unmatched" or
+// "failed"), the best title similarity of the results per source name, the
+// reasons of the failures, the number of merged publications, the
+// mismatches and findings that a maintainer should check, and the name of
+// the source that set each field (see fieldValues). Publications a source
+// added get its name as their source.
+export async function updatePaper(paper, sources = enabledSources()) {
+  // the paper file may rule out sources, like arXiv for a book
+  sources = sources.filter(
+    (source) => !(paper.skip_sources || []).includes(source.key)
+  );
+  let result = {
+    sources: {},
+    scores: {},
+    failures: [],
+    mergedPublications: 0,
+    mismatches: [],
+    attention: [],
+    provenance: {},
+  };
+  const initial = fieldValues(paper);
+  // Sources run one after another, since later ones fill in blanks left by
+  // the earlier ones. They return whether they found the paper.
+  for (const source of sources) {
+    const before = fieldValues(paper);
+    const known = new Set(paper.publications);
+    try {
+      result.sources[source.name] = (await source.update(
+        paper,
+        config.sources[source.key]
+      ))
+        ? "matched"
+        : "unmatched";
+    } catch (error) {
+      result.sources[source.name] = "failed";
+      const reason = error.response
+        ? "HTTP " + error.response.status
+        : error.code || error.message;
+      // other errors are mistakes of the updater
+      const kind = error instanceof UpdaterError ? error.kind : "internal";
+      result.failures.push({ source: source.name, reason, kind });
+      log.error(
+        "Failed to fetch data from " +
+          source.name +
+          " for the paper: " +
+          paper.title +
+          " (" +
+          reason +
+          (error.retries ? ", after " + error.retries + " retries" : "") +
+          ")"
+      );
+    }
+    paper.publications
+      .filter((pub) => !known.has(pub) && !("source" in pub))
+      .forEach((pub) => {
+        pub.source = source.name;
+      });
+    fieldValues(paper).forEach((value, field) => {
+      if (before.get(field) !== value) {
+        result.provenance[field] = source.name;
+      }
+    });
+  }
+  result.scores = { ...bestScores.get(paper) };
+  result.mergedPublications = dedupePublications(paper);
+  // only publications the sources added or changed get their kind, so that
+  // an update that found nothing new leaves the paper file as it was
+  const touched = (pub) =>
+    Object.keys(pub).some(
+      (field) =>
+        initial.get(pub.name + " " + field) !== JSON.stringify(pub[field])
+    );
+  paper.publications
+    .filter(
+      (pub) =>
+        !("kind" in pub) && publicationKind(pub) !== undefined && touched(pub)
+    )
+    .forEach((pub) => {
+      pub.kind = publicationKind(pub);
+    });
+  sortPublications(paper);
+  result.mismatches = [
+    ...checkBibtex(paper, config.sources.dblp.threshold),
+    ...crossCheck(paper),
+  ];
+  result.mismatches.forEach((reason) => log.warn(paper.title + ": " + reason));
+  const searchedDblp = sources.some((source) => source.key === "dblp");
+  result.attention = [
+    ...needsAttention(paper, searchedDblp),
+    ...(authorMatches.get(paper) || []),
+  ];
+  result.attention.forEach((reason) =>
+    log.warn("Needs attention: " + paper.title + ": " + reason)
+  );
+  return result;
+}
@@ -0,0 +1,551 @@
+// Tests of the updater, run with `npm test`. The sources answer from canned
+// responses instead of the network.
+
+import assert from "node:assert";
+import fs from "fs";
+import os from "os";
+import path from "path";
+import test from "node:test";
+import {
+  allSources,
+  announcedVenue,
+  canonicalizeVenue,
+  configure,
+  decodeEntities,
+  hasCompleteMetadata,
+  http,
+  lastNameKey,
+  paperSlug,
+  paperSlugs,
+  resetField,
+  unknownFields,
+  updatePaper,
+} from "./updater.mjs";
+
+// An empty fixture directory turns off the cache and the rate limits; the
+// responses come from serve.
+configure({
+  fixturesDir: fs.mkdtempSync(path.join(os.tmpdir(), "updater-test-")),
+  logLevel: "none",
+});
+
+// Answers the requests of the updater with the response of the first url
+// prefix the request url starts with, and with 404 if there is none.
+function serve(responses) {
+  http.request = async ({ url, validateStatus }) => {
+    const prefix = Object.keys(responses).find((p) => url.startsWith(p));
+    const response = {
+      status: prefix === undefined ? 404 : 200,
+      data: prefix === undefined ? undefined : responses[prefix],
+      request: { res: { responseUrl: url } },
+    };
+    if (!validateStatus(response.status)) {
+      let error = new Error("Request failed with status " + response.status);
+      error.response = response;
+      throw error;
+    }
+    return response;
+  };
+}
+
+const sources = (...keys) =>
+  allSources.filter((source) => keys.includes(source.key));
+
+const arxivApi = "http://export.arxiv.org/api/query";
+const dblpSearch = "https://dblp.org/search/publ/api";
+
+// An arXiv API response with the given entries.
+const arxivEntries = (...entries) =>
+  '<?xml version="1.0"?><feed xmlns="http://www.w3.org/2005/Atom"' +
+  ' xmlns:opensearch="http://a9.com/-/spec/opensearch/1.1/"' +
+  ' xmlns:arxiv="http://arxiv.org/schemas/atom">' +
+  "<opensearch:totalResults>" +
+  entries.length +
+  "</opensearch:totalResults>" +
+  entries
+    .map(
+      (entry) =>
+        "<entry><id>http://arxiv.org/abs/" +
+        entry.id +
+        "v1</id><published>" +
+        entry.published +
+        "</published><updated>" +
+        entry.published +
+        "</updated><title>" +
+        entry.title +
+        "</title><summary>" +
+        entry.summary +
+        "</summary>" +
+        entry.authors
+          .map((name) => "<author><name>" + name + "</name></author>")
+          .join("") +
+        (entry.comment
+          ? "<arxiv:comment>" + entry.comment + "</arxiv:comment>"
+          : "") +
+        '<arxiv:primary_category term="cs.DS"/></entry>'
+    )
+    .join("") +
+  "</feed>";
+
+// A DBLP search response with the given hits.
+const dblpHits = (...hits) =>
+  '<?xml version="1.0"?><result><hits total="' +
+  hits.length +
+  '">' +
+  hits
+    .map(
+      (hit) =>
+        "<hit><info><authors>" +
+        hit.authors
+          .map(
+            ([name, orcid]) =>
+              "<author" +
+              (orcid ? ' orcid="' + orcid + '"' : "") +
+              ">" +
+              name +
+              "</author>"
+          )
+          .join("") +
+        "</authors><title>" +
+        hit.title +
+        "</title><venue>" +
+        hit.venue +
+        "</venue><year>" +
+        hit.year +
+        "</year><type>" +
+        hit.type +
+        "</type><key>" +
+        hit.key +
+        "</key>" +
+        (hit.ee ? "<ee>" + hit.ee + "</ee>" : "") +
+        "</info></hit>"
+    )
+    .join("") +
+  "</hits></result>";
+
+const dblpBibtex = (key, title, year) =>
+  "@inproceedings{DBLP:" +
+  key +
+  ",\n  title     = {" +
+  title +
+  "},\n  year      = {" +
+  year +
+  "}\n}\n";
+
+const title = "Online Sorting with Predictions";
+const sodaHit = {
+  key: "conf/soda/AamandAB22",
+  title: title + ".",
+  venue: "SODA",
+  year: 2022,
+  type: "Conference and Workshop Papers",
+  ee: "https://doi.org/10.1137/1.9781611977073.1",
+  authors: [
+    ["Anders Aamand", "0000-0002-0402-0514"],
+    ["Mikkel Abrahamsen 0001"],
+    ["Lorenzo Beretta"],
+  ],
+};
+
+const arxivEntry = {
+  id: "2110.01234",
+  published: "2021-11-04T17:00:00Z",
+  title,
+  summary: "We study sorting with predictions.",
+  authors: ["Anders Aamand", "Mikkel Abrahamsen", "Lorenzo Beretta"],
+};
+
+const newPaper = (fields) => ({
+  title,
+  authors: "Aamand, Abrahamsen, Beretta",
+  labels: ["online"],
+  publications: [{ name: "arXiv", year: 2021, month: 10, day: 4 }],
+  ...fields,
+});
+
+test("DBLP adds the publication of an exact match", async () => {
+  serve({
+    [dblpSearch]: dblpHits(sodaHit),
+    "https://dblp.org/rec/conf/soda/AamandAB22.bib": dblpBibtex(
+      sodaHit.key,
+      title,
+      2022
+    ),
+  });
+  const paper = newPaper();
+  const result = await updatePaper(paper, sources("dblp"));
+  assert.strictEqual(result.sources.DBLP, "matched");
+  const soda = paper.publications.find((pub) => pub.name === "SODA");
+  assert.strictEqual(soda.year, 2022);
+  assert.strictEqual(soda.kind, "conference");
+  assert.strictEqual(soda.url, sodaHit.ee);
+  assert.strictEqual(soda.dblp_key, sodaHit.key);
+  assert.strictEqual(soda.source, "DBLP");
+  assert.strictEqual(soda.match_confidence, 1);
+  assert.match(soda.bibtex, /^@inproceedings\{DBLP:conf\/soda\/AamandAB22,/);
+});
+
+test("DBLP leaves a paper without a similar result alone", async () => {
+  serve({
+    [dblpSearch]: dblpHits({
+      ...sodaHit,
+      title: "Sorting Networks for Streaming Graphs.",
+    }),
+  });
+  const paper = newPaper();
+  const result = await updatePaper(paper, sources("dblp"));
+  assert.strictEqual(result.sources.DBLP, "unmatched");
+  // not even the kind of the preprint is added
+  assert.deepStrictEqual(paper, newPaper());
+});
+
+test("DBLP skips CoRR records", async () => {
+  serve({
+    [dblpSearch]: dblpHits({
+      ...sodaHit,
+      key: "journals/corr/abs-2110-01234",
+      venue: "CoRR",
+      year: 2021,
+      type: "Informal and Other Publications",
+    }),
+  });
+  const paper = newPaper();
+  const result = await updatePaper(paper, sources("dblp"));
+  assert.strictEqual(result.sources.DBLP, "unmatched");
+  assert.deepStrictEqual(
+    paper.publications.map((pub) => pub.name),
+    ["arXiv"]
+  );
+});
+
+test("DBLP fills in the authors of a paper without them", async () => {
+  serve({
+    [dblpSearch]: dblpHits(sodaHit),
+    "https://dblp.org/rec/": dblpBibtex(sodaHit.key, title, 2022),
+  });
+  const paper = newPaper();
+  delete paper.authors;
+  await updatePaper(paper, sources("dblp"));
+  // without the suffix like " 0001" of homonymous authors on DBLP
+  assert.strictEqual(paper.authors, "Aamand, Abrahamsen, Beretta");
+  assert.deepStrictEqual(paper.authors_detailed, [
+    { name: "Anders Aamand", orcid: "0000-0002-0402-0514" },
+    { name: "Mikkel Abrahamsen" },
+    { name: "Lorenzo Beretta" },
+  ]);
+});
+
+test("DBLP completes an existing publication at the venue", async () => {
+  serve({
+    [dblpSearch]: dblpHits(sodaHit),
+    "https://dblp.org/rec/": dblpBibtex(sodaHit.key, title, 2022),
+  });
+  const url = "https://epubs.siam.org/doi/10.1137/1.9781611977073.1";
+  const paper = newPaper();
+  paper.publications.push({ name: "SODA", year: 2022, url });
+  const result = await updatePaper(paper, sources("dblp"));
+  assert.strictEqual(result.mergedPublications, 0);
+  const soda = paper.publications.filter((pub) => pub.name === "SODA");
+  assert.strictEqual(soda.length, 1);
+  assert.strictEqual(soda[0].url, url);
+  assert.strictEqual(soda[0].dblp_key, sodaHit.key);
+  assert.ok("bibtex" in soda[0]);
+  // it was entered by hand
+  assert.ok(!("source" in soda[0]));
+});
+
+test("accents do not tell authors apart", async () => {
+  assert.strictEqual(
+    lastNameKey("Thodoris Lýkouris"),
+    lastNameKey("Thodoris Lykouris")
+  );
+  assert.strictEqual(
+    lastNameKey("Michał Włodarczyk"),
+    lastNameKey("Michal Wlodarczyk")
+  );
+  assert.notStrictEqual(
+    lastNameKey("Thodoris Lykouris"),
+    lastNameKey("Thodoris Lykos")
+  );
+
+  const crossChecked = async (arxivAuthors, dblpAuthors) => {
+    serve({
+      [arxivApi]: arxivEntries({ ...arxivEntry, authors: arxivAuthors }),
+      [dblpSearch]: dblpHits({
+        ...sodaHit,
+        authors: dblpAuthors.map((name) => [name]),
+      }),
+      "https://dblp.org/rec/": dblpBibtex(sodaHit.key, title, 2022),
+    });
+    const paper = newPaper({ publications: [] });
+    const result = await updatePaper(paper, sources("arxiv", "dblp"));
+    return result.mismatches.filter((reason) => /differ/.test(reason));
+  };
+  assert.deepStrictEqual(
+    await crossChecked(
+      ["Thodoris Lýkouris", "Michał Włodarczyk"],
+      ["Thodoris Lykouris", "Michal Wlodarczyk"]
+    ),
+    []
+  );
+  assert.strictEqual(
+    (
+      await crossChecked(
+        ["Thodoris Lýkouris", "Michał Włodarczyk"],
+        ["Anders Aamand", "Lorenzo Beretta"]
+      )
+    ).length,
+    1
+  );
+});
+
+test("DBLP keeps the conference and the journal version", async () => {
+  const stocHit = {
+    ...sodaHit,
+    key: "conf/stoc/AamandAB21",
+    venue: "STOC",
+    year: 2021,
+    ee: "https://doi.org/10.1145/3406325.3451001",
+  };
+  const jacmHit = {
+    ...sodaHit,
+    key: "journals/jacm/AamandAB23",
+    venue: "J. ACM",
+    year: 2023,
+    type: "Journal Articles",
+    ee: "https://doi.org/10.1145/3571234",
+  };
+  serve({
+    [dblpSearch]: dblpHits(stocHit, jacmHit),
+    "https://dblp.org/rec/": dblpBibtex(sodaHit.key, title, 2022),
+  });
+  const paper = newPaper();
+  const result = await updatePaper(paper, sources("dblp"));
+  assert.strictEqual(result.mergedPublications, 0);
+  // the preprint (which DBLP left alone) first, then the newest version
+  assert.deepStrictEqual(
+    paper.publications.map(({ name, kind, year, dblp_key }) => ({
+      name,
+      kind,
+      year,
+      dblp_key,
+    })),
+    [
+      { name: "arXiv", kind: undefined, year: 2021, dblp_key: undefined },
+      { name: "J. ACM", kind: "journal", year: 2023, dblp_key: jacmHit.key },
+      { name: "STOC", kind: "conference", year: 2021, dblp_key: stocHit.key },
+    ]
+  );
+});
+
+test("arXiv updates a preprint entered by hand in place", async () => {
+  serve({ [arxivApi]: arxivEntries(arxivEntry) });
+  const paper = newPaper({
+    publications: [
+      { name: "arXiv", year: 2021, url: "https://arxiv.org/abs/2110.01234" },
+    ],
+  });
+  const result = await updatePaper(paper, sources("arxiv"));
+  assert.strictEqual(result.sources.arXiv, "matched");
+  assert.strictEqual(paper.publications.length, 1);
+  const [arxiv] = paper.publications;
+  assert.strictEqual(arxiv.month, 10);
+  assert.ok(!("source" in arxiv));
+  assert.ok(!("match_confidence" in arxiv));
+  assert.strictEqual(result.provenance["arXiv month"], "arXiv");
+});
+
+test("fields the updater does not know are reported", () => {
+  const paper = newPaper({ notes: "check the journal version" });
+  paper.publications[0].notes = "v2 fixes a proof";
+  assert.deepStrictEqual(unknownFields(paper), [
+    "notes",
+    "publication 1: notes",
+  ]);
+  assert.deepStrictEqual(unknownFields(newPaper()), []);
+});
+
+test("slugs are ascii and distinct", () => {
+  assert.strictEqual(
+    paperSlug(newPaper({ title: "Łukasz's Müller–Schäfer Scheduling" })),
+    "lukasz-s-muller-schafer-scheduling-2021"
+  );
+  const slugs = paperSlugs([
+    { id: "Lin22.yml", paper: newPaper() },
+    { id: "Lin22a.yml", paper: newPaper() },
+    { id: "Other22.yml", paper: newPaper({ title: "Paging" }) },
+  ]);
+  const [first, second, other] = [...slugs.values()];
+  assert.match(first, /^online-sorting-with-predictions-2021-[0-9a-f]{6}$/);
+  assert.match(second, /^online-sorting-with-predictions-2021-[0-9a-f]{6}$/);
+  assert.notStrictEqual(first, second);
+  assert.strictEqual(other, "paging-2021");
+  // a paper keeps its slug whatever the order of the papers
+  const reversed = paperSlugs([
+    { id: "Lin22a.yml", paper: newPaper() },
+    { id: "Lin22.yml", paper: newPaper() },
+  ]);
+  assert.strictEqual(reversed.get("Lin22.yml"), first);
+  assert.strictEqual(reversed.get("Lin22a.yml"), second);
+});
+
+test("entities in venues are decoded", async () => {
+  assert.strictEqual(
+    decodeEntities("Theory &amp; Practice"),
+    "Theory & Practice"
+  );
+  // DBLP encodes some venues twice
+  assert.strictEqual(
+    decodeEntities("Theory &amp;amp; Practice"),
+    "Theory & Practice"
+  );
+  assert.strictEqual(decodeEntities("R&D"), "R&D");
+  // the key of a decoded venue has no "amp" left
+  assert.strictEqual(
+    canonicalizeVenue(decodeEntities("ICALP &amp;amp; LICS")),
+    canonicalizeVenue("ICALP & LICS")
+  );
+
+  // the XML of the responses encodes them once more
+  const venues = ["Theory &amp;amp; Practice", "Theory &amp;amp;amp; Practice"];
+  for (const venue of venues) {
+    serve({
+      [dblpSearch]: dblpHits({ ...sodaHit, key: "conf/tap/X22", venue }),
+      "https://dblp.org/rec/": dblpBibtex(sodaHit.key, title, 2022),
+    });
+    const paper = newPaper();
+    await updatePaper(paper, sources("dblp"));
+    assert.deepStrictEqual(
+      paper.publications.map((pub) => pub.name),
+      ["arXiv", "Theory & Practice"]
+    );
+  }
+});
+
+test("arXiv comments announce accepted papers", async () => {
+  assert.deepStrictEqual(announcedVenue("To appear in SODA 2024"), {
+    name: "SODA",
+    year: 2024,
+  });
+  assert.deepStrictEqual(
+    announcedVenue(
+      "Accepted at the 35th ACM-SIAM Symposium on Discrete Algorithms " +
+        "(SODA 2024). 30 pages"
+    ),
+    { name: "SODA", year: 2024 }
+  );
+  assert.deepStrictEqual(announcedVenue("accepted to ICML 2023"), {
+    name: "ICML",
+    year: 2023,
+  });
+  // published papers are left to DBLP
+  assert.strictEqual(announcedVenue("Published in SODA 2024"), undefined);
+  assert.strictEqual(announcedVenue("Appeared in SODA 2024"), undefined);
+  assert.strictEqual(announcedVenue("Not accepted in SODA 2024"), undefined);
+  assert.strictEqual(
+    announcedVenue("This version has not been accepted at SODA 2024"),
+    undefined
+  );
+  assert.strictEqual(
+    announcedVenue("Accepted at the NeurIPS 2023 Workshop on ML for Systems"),
+    undefined
+  );
+  // without a year or a known venue
+  assert.strictEqual(announcedVenue("To appear in SODA"), undefined);
+  assert.strictEqual(announcedVenue("To appear in FOOBAR 2024"), undefined);
+
+  serve({
+    [arxivApi]: arxivEntries({
+      ...arxivEntry,
+      comment: "To appear in SODA 2022",
+    }),
+  });
+  const paper = newPaper({ publications: [] });
+  await updatePaper(paper, sources("arxiv"));
+  const soda = paper.publications.find((pub) => pub.name === "SODA");
+  assert.strictEqual(soda.year, 2022);
+  assert.strictEqual(soda.to_appear, true);
+  assert.strictEqual(soda.source, "arXiv");
+  assert.strictEqual(soda.match_confidence, 1);
+});
+
+test("Semantic Scholar files DOIs under the right version", async () => {
+  const semanticScholar = (hit) =>
+    serve({
+      "https://api.semanticscholar.org/": {
+        data: [{ title, year: 2021, citationCount: 7, ...hit }],
+      },
+    });
+  const stocPaper = () =>
+    newPaper({
+      publications: [
+        { name: "arXiv", year: 2021 },
+        { name: "STOC", year: 2021, dblp_key: "conf/stoc/AamandAB21" },
+      ],
+    });
+
+  // the venue is matched by its canonical name
+  semanticScholar({
+    venue: "Symposium on Theory of Computing",
+    publicationTypes: ["Conference"],
+    externalIds: { DOI: "10.1145/3406325.3451001" },
+  });
+  let paper = stocPaper();
+  await updatePaper(paper, sources("semantic_scholar"));
+  let [arxiv, stoc] = paper.publications;
+  assert.strictEqual(stoc.doi, "10.1145/3406325.3451001");
+  assert.strictEqual(stoc.citation_count, 7);
+  assert.ok(!("doi" in arxiv));
+
+  // a journal record is not the conference version, and its DOI does not
+  // go to the preprint either
+  semanticScholar({
+    venue: "Journal of Unknown Results",
+    publicationTypes: ["JournalArticle"],
+    externalIds: { DOI: "10.1000/jur.2021.1" },
+  });
+  paper = stocPaper();
+  await updatePaper(paper, sources("semantic_scholar"));
+  [arxiv, stoc] = paper.publications;
+  assert.ok(!("doi" in stoc));
+  assert.ok(!("doi" in arxiv));
+  assert.strictEqual(arxiv.citation_count, 7);
+
+  // the DOI arXiv registers goes to the preprint
+  semanticScholar({
+    venue: "arXiv.org",
+    externalIds: { DOI: "10.48550/arXiv.2110.01234" },
+  });
+  paper = stocPaper();
+  await updatePaper(paper, sources("semantic_scholar"));
+  [arxiv, stoc] = paper.publications;
+  assert.strictEqual(arxiv.doi, "10.48550/arXiv.2110.01234");
+  assert.ok(!("doi" in stoc));
+});
+
+test("metadata is complete with a peer-reviewed version with bibtex", () => {
+  const soda = { name: "SODA", year: 2022, url: sodaHit.ee };
+  const withSoda = (pub) =>
+    newPaper({ publications: [...newPaper().publications, pub] });
+  assert.strictEqual(hasCompleteMetadata(newPaper()), false);
+  assert.strictEqual(hasCompleteMetadata(withSoda(soda)), false);
+  assert.strictEqual(
+    hasCompleteMetadata(withSoda({ ...soda, bibtex: "@inproceedings{}" })),
+    true
+  );
+});
+
+test("resetting a field clears it on the paper and its publications", () => {
+  const paper = newPaper({
+    citation_count: 12,
+    publications: [
+      { ...newPaper().publications[0], citation_count: 3 },
+      { name: "SODA", year: 2022, citation_count: 9, bibtex: "@x{}" },
+    ],
+  });
+  assert.strictEqual(resetField(paper, "citation_count"), 3);
+  assert.ok(!("citation_count" in paper));
+  assert.ok(paper.publications.every((pub) => !("citation_count" in pub)));
+  assert.strictEqual(resetField(paper, "bibtex"), 1);
+  assert.strictEqual(paper.publications[1].year, 2022);
+});
@@ -0,0 +1,17 @@
+/* eslint-disable react/prop-types */
+import React from "react";
+import PaperList from "../components/paperlist";
+import Layout from "../components/layout";
+import "@fontsource/roboto/400.css";
+
+export default function Template({ pageContext }) {
+  const { paper } = pageContext;
+  return (
+    <>
+      <title>ALPS - {paper.title}</title>
+      <Layout>
+        <PaperList data={[paper]} />
+      </Layout>
+    </>
+  );
+}